@@ -1,5 +1,6 @@
 import { useEffect, useMemo, useState } from "react";
-import { invoke } from "@tauri-apps/api/core";
+import type { MouseEvent } from "react";
+import { convertFileSrc, invoke } from "@tauri-apps/api/core";
 
 type Movie = {
   id: number;
@@ -10,31 +11,78 @@ type Movie = {
   poster_path?: string | null;
 };
 
+type Show = {
+  id: number;
+  name: string;
+  first_air_date?: string | null;
+  poster_path?: string | null;
+};
+
+type Episode = {
+  series_title: string;
+  season: number;
+  episode: number;
+};
+
 type EnrichedEntry = {
   watched_at?: string | null;
   raw_title: string;
   cleaned_title: string;
   release_year?: number | null;
+  is_stream?: boolean;
+  episode?: Episode | null;
   movie?: Movie | null;
+  show?: Show | null;
+  confidence?: number | null;
+  low_confidence?: boolean;
+  source_path?: string | null;
   tmdb_url?: string | null;
+  imdb_url?: string | null;
   poster_url?: string | null;
 };
 
+type CleanProfile = "standard" | "scene" | "plex" | "raw";
+
 type AppSettings = {
   log_path?: string | null;
   cache_path?: string | null;
+  clean_profile?: CleanProfile | null;
   tmdb_key_present?: boolean | null;
+  path_warning?: string | null;
 };
 
 type SettingsInput = {
   log_path?: string | null;
   cache_path?: string | null;
+  clean_profile: CleanProfile;
   tmdb_api_key?: string | null;
 };
 
+type CacheStatus =
+  | { kind: "ok" }
+  | { kind: "load_corrupted"; path: string }
+  | { kind: "newer_version"; path: string; version: number }
+  | { kind: "save_failed"; path: string; reason: string }
+  | { kind: "unwritable"; path: string; reason: string };
+
 type HistoryPayload = {
   entries: EnrichedEntry[];
+  total?: number;
   cache_warning?: string | null;
+  cache_status?: CacheStatus | null;
+  path_warning?: string | null;
+  overrides_warning?: string | null;
+  resolved_log_path?: string;
+  log_existed?: boolean;
+  resolved_cache_path?: string;
+  cache_existed?: boolean;
+};
+
+type HistorySources = {
+  logPath: string;
+  logExisted: boolean;
+  cachePath: string;
+  cacheExisted: boolean;
 };
 
 type GroupedEntry = {
@@ -65,6 +113,14 @@ function releaseYear(value?: string | null) {
   return /^\d{4}$/.test(year) ? year : null;
 }
 
+// Posters downloaded by the backend are local paths, served through the asset protocol.
+function posterSrc(value: string | null) {
+  if (!value || /^https?:\/\//.test(value)) {
+    return value;
+  }
+  return convertFileSrc(value);
+}
+
 function normalizeSetting(value: string | null | undefined) {
   if (!value) {
     return null;
@@ -113,10 +169,16 @@ function formatWatchDates(values: string[]) {
 export default function App() {
   const [entries, setEntries] = useState<EnrichedEntry[]>([]);
   const [warning, setWarning] = useState<string | null>(null);
+  const [cacheRetryable, setCacheRetryable] = useState(false);
+  const [pathWarning, setPathWarning] = useState<string | null>(null);
+  const [overridesWarning, setOverridesWarning] = useState<string | null>(null);
+  const [sources, setSources] = useState<HistorySources | null>(null);
   const [error, setError] = useState<string | null>(null);
   const [status, setStatus] = useState<"loading" | "idle" | "error">("loading");
   const [logPath, setLogPath] = useState("");
   const [cachePath, setCachePath] = useState("");
+  const [openedLogPath, setOpenedLogPath] = useState<string | null>(null);
+  const [cleanProfile, setCleanProfile] = useState<CleanProfile>("standard");
   const [tmdbApiKey, setTmdbApiKey] = useState("");
   const [tmdbKeyPresent, setTmdbKeyPresent] = useState(false);
   const [showSettings, setShowSettings] = useState(false);
@@ -125,26 +187,46 @@ export default function App() {
   const buildSettingsPayload = (overrides?: Partial<AppSettings>): SettingsInput => ({
     log_path: normalizeSetting(resolveSetting(overrides, "log_path", logPath)),
     cache_path: normalizeSetting(resolveSetting(overrides, "cache_path", cachePath)),
+    clean_profile: cleanProfile,
     tmdb_api_key: normalizeSetting(tmdbApiKey),
   });
 
-  const saveSettings = async () => {
+  const saveSettings = async (skipValidation = false) => {
     const payload = buildSettingsPayload();
-    await invoke("save_settings", { settings: payload });
+    await invoke("save_settings", { settings: payload, skipValidation });
   };
 
-  const loadHistory = async (overrides?: Partial<AppSettings>) => {
+  const loadHistory = async (
+    overrides?: Partial<AppSettings>,
+    command: "load_history" | "retry_unmatched" = "load_history"
+  ) => {
     setStatus("loading");
     setError(null);
     const settingsPayload = buildSettingsPayload(overrides);
     try {
-      const payload = await invoke<HistoryPayload>("load_history", {
+      const payload = await invoke<HistoryPayload>(command, {
         logPath: settingsPayload.log_path,
         cachePath: settingsPayload.cache_path,
         tmdbApiKey: settingsPayload.tmdb_api_key,
       });
       setEntries(payload.entries ?? []);
       setWarning(payload.cache_warning ?? null);
+      setCacheRetryable(
+        payload.cache_status?.kind === "save_failed" ||
+          payload.cache_status?.kind === "unwritable"
+      );
+      setPathWarning(payload.path_warning ?? null);
+      setOverridesWarning(payload.overrides_warning ?? null);
+      setSources(
+        payload.resolved_log_path && payload.resolved_cache_path
+          ? {
+              logPath: payload.resolved_log_path,
+              logExisted: payload.log_existed ?? false,
+              cachePath: payload.resolved_cache_path,
+              cacheExisted: payload.cache_existed ?? false,
+            }
+          : null
+      );
       setStatus("idle");
     } catch (err) {
       const message = err instanceof Error ? err.message : String(err);
@@ -159,7 +241,9 @@ export default function App() {
         const settings = await invoke<AppSettings>("load_settings");
         setLogPath(settings.log_path ?? "");
         setCachePath(settings.cache_path ?? "");
+        setCleanProfile(settings.clean_profile ?? "standard");
         setTmdbKeyPresent(Boolean(settings.tmdb_key_present));
+        setPathWarning(settings.path_warning ?? null);
         setTmdbApiKey("");
         await loadHistory(settings);
       } catch {
@@ -169,11 +253,23 @@ export default function App() {
     void init();
   }, []);
 
+  const reportError = (err: unknown) => {
+    const message = err instanceof Error ? err.message : String(err);
+    setError(message);
+    setStatus("error");
+  };
+
+  const openTmdb = (event: MouseEvent, tmdbUrl: string) => {
+    event.preventDefault();
+    invoke("open_tmdb", { tmdbUrl }).catch(reportError);
+  };
+
   const items = useMemo(() => {
     const byKey = new Map<string, GroupedEntry>();
     const order: string[] = [];
-    const reversed = entries.slice().reverse();
-    reversed.forEach(entry => {
+    // Entries arrive newest first, so each title keeps its latest watch as the
+    // representative and lists its watch dates newest first.
+    entries.forEach(entry => {
       const year = entry.release_year ?? releaseYear(entry.movie?.release_date ?? null);
       const key = `${entry.cleaned_title.toLowerCase()}${year ? `|${year}` : ""}`;
       const existing = byKey.get(key);
@@ -243,6 +339,14 @@ export default function App() {
                   onChange={(event) => setLogPath(event.target.value)}
                   placeholder="Auto-detect or set GOO_LOG_PATH"
                 />
+                {sources ? (
+                  <span className="hint">
+                    Reading {sources.logPath}
+                    {sources.logExisted ? "" : " (not created yet)"}
+                  </span>
+                ) : (
+                  openedLogPath && <span className="hint">Log file: {openedLogPath}</span>
+                )}
               </label>
               <label className="field">
                 <span>Cache path</span>
@@ -251,6 +355,24 @@ export default function App() {
                   onChange={(event) => setCachePath(event.target.value)}
                   placeholder="Optional .goo_cache.json"
                 />
+                {sources && (
+                  <span className="hint">
+                    Using {sources.cachePath}
+                    {sources.cacheExisted ? "" : " (started fresh)"}
+                  </span>
+                )}
+              </label>
+              <label className="field">
+                <span>Filename style</span>
+                <select
+                  value={cleanProfile}
+                  onChange={(event) => setCleanProfile(event.target.value as CleanProfile)}
+                >
+                  <option value="standard">Mixed (default)</option>
+                  <option value="scene">Scene releases</option>
+                  <option value="plex">Plex-renamed</option>
+                  <option value="raw">Raw (minimal cleaning)</option>
+                </select>
               </label>
               <label className="field">
                 <span>TMDB API Key</span>
@@ -258,11 +380,29 @@ export default function App() {
                   type="password"
                   value={tmdbApiKey}
                   onChange={(event) => setTmdbApiKey(event.target.value)}
-                  placeholder={tmdbKeyPresent ? "Saved in Windows Credential Manager" : "Optional (or set TMDB_API_KEY env)"}
+                  placeholder={tmdbKeyPresent ? "Saved in the OS credential store" : "Optional (or set TMDB_API_KEY env)"}
                 />
               </label>
             </div>
             <div className="modal-footer">
+              <button
+                className="secondary"
+                onClick={() => {
+                  invoke<string>("reveal_path", {
+                    which: "log",
+                    logPath: logPath.trim() ? logPath.trim() : null,
+                    cachePath: null,
+                  })
+                    .then(setOpenedLogPath)
+                    .catch(err => {
+                      const message = err instanceof Error ? err.message : String(err);
+                      setError(message);
+                      setStatus("error");
+                    });
+                }}
+              >
+                Open Folder
+              </button>
               {tmdbKeyPresent && (
                 <button
                   className="secondary"
@@ -307,6 +447,17 @@ export default function App() {
               >
                 Clear Log
               </button>
+              <button
+                className="secondary"
+                onClick={() => {
+                  setShowSettings(false);
+                  void loadHistory(undefined, "retry_unmatched");
+                }}
+                disabled={status === "loading"}
+                title="Search TMDB again for titles that found no match"
+              >
+                Retry Unmatched
+              </button>
               <button className="secondary" onClick={() => setShowSettings(false)}>
                 Cancel
               </button>
@@ -336,7 +487,18 @@ export default function App() {
         </div>
       )}
 
-      {warning && <div className="banner warning">Cache: {warning}</div>}
+      {pathWarning && <div className="banner warning">Paths: {pathWarning}</div>}
+      {overridesWarning && <div className="banner warning">Overrides: {overridesWarning}</div>}
+      {warning && (
+        <div className="banner warning">
+          Cache: {warning}
+          {cacheRetryable && (
+            <button className="secondary" onClick={() => loadHistory()}>
+              Retry
+            </button>
+          )}
+        </div>
+      )}
       {error && (
         <div className="banner error">
           {error}
@@ -357,7 +519,7 @@ export default function App() {
       <section className="grid">
         {items.map((item, index) => {
           const entry = item.entry;
-          const title = entry.movie?.title ?? entry.cleaned_title;
+          const title = entry.movie?.title ?? entry.show?.name ?? entry.cleaned_title;
           const year = entry.release_year ?? releaseYear(entry.movie?.release_date ?? null);
           const dateInfo = formatWatchDates(item.watch_dates);
           const dateTitle = dateInfo.full !== dateInfo.text ? dateInfo.full : undefined;
@@ -366,11 +528,17 @@ export default function App() {
             `https://www.themoviedb.org/search?query=${encodeURIComponent(
               entry.cleaned_title
             )}`;
-          const poster = entry.poster_url ?? null;
+          const poster = posterSrc(entry.poster_url ?? null);
 
           return (
             <article className="card" key={`${entry.raw_title}-${index}`}>
-              <a className="poster" href={tmdbLink} target="_blank" rel="noreferrer">
+              <a
+                className="poster"
+                href={tmdbLink}
+                target="_blank"
+                rel="noreferrer"
+                onClick={(event) => openTmdb(event, tmdbLink)}
+              >
                 {poster ? (
                   <img src={poster} alt={`${title} poster`} loading="lazy" />
                 ) : (
@@ -384,15 +552,41 @@ export default function App() {
                 <div className="title-row">
                   <h3 title={title}>{title}</h3>
                   {year && <span className="badge">{year}</span>}
+                  {entry.low_confidence && (
+                    <span className="badge" title="This match may be wrong">
+                      ?
+                    </span>
+                  )}
                 </div>
                 <div className="meta">
                   <span className="meta-item" title={dateTitle}>
                     {dateInfo.text}
                   </span>
                   <div className="meta-actions">
-                    <a className="tmdb-link" href={tmdbLink} target="_blank" rel="noreferrer">
+                    <a
+                      className="tmdb-link"
+                      href={tmdbLink}
+                      target="_blank"
+                      rel="noreferrer"
+                      onClick={(event) => openTmdb(event, tmdbLink)}
+                    >
                       TMDB
                     </a>
+                    {entry.source_path && (
+                      <button
+                        className="meta-button"
+                        onClick={() => {
+                          invoke("play_in_vlc", { sourcePath: entry.source_path }).catch(
+                            reportError
+                          );
+                        }}
+                        title="Play again in VLC"
+                      >
+                        <svg width="14" height="14" viewBox="0 0 24 24" fill="currentColor">
+                          <path d="M7 4l13 8-13 8z" />
+                        </svg>
+                      </button>
+                    )}
                     <button
                       className="meta-button"
                       onClick={async () => {
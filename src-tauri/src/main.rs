@@ -3,76 +3,234 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri_plugin_opener::OpenerExt;
 
 #[derive(Serialize)]
 struct HistoryPayload {
+    /// One page, newest watch first.
     entries: Vec<goo::enrich::EnrichedEntry>,
+    /// Entries across all pages.
+    total: usize,
     cache_warning: Option<String>,
+    cache_status: goo::enrich::CacheStatus,
+    path_warning: Option<String>,
+    overrides_warning: Option<String>,
+    /// The log and cache actually read once defaults were applied, and
+    /// whether each existed at the time.
+    resolved_log_path: String,
+    log_existed: bool,
+    resolved_cache_path: String,
+    cache_existed: bool,
+}
+
+/// The last enriched history, so paging past the first page slices it
+/// instead of enriching the whole log again.
+#[derive(Default)]
+struct HistorySnapshot(Mutex<Option<LoadedHistory>>);
+
+struct LoadedHistory {
+    log_path: PathBuf,
+    cache_path: Option<String>,
+    entries: Vec<goo::enrich::EnrichedEntry>,
+    cache_warning: Option<String>,
+    cache_status: goo::enrich::CacheStatus,
+    path_warning: Option<String>,
+    overrides_warning: Option<String>,
+    resolved_log_path: PathBuf,
+    log_existed: bool,
+    resolved_cache_path: PathBuf,
+    cache_existed: bool,
+}
+
+#[derive(Serialize)]
+struct DashboardPayload {
+    entries: Vec<goo::enrich::EnrichedEntry>,
+    cache_warning: Option<String>,
+    cache_status: goo::enrich::CacheStatus,
+    path_warning: Option<String>,
+    overrides_warning: Option<String>,
+    stats: goo::stats::WatchStats,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 struct StoredSettings {
     log_path: Option<String>,
     cache_path: Option<String>,
+    #[serde(default)]
+    clean_profile: goo::CleanProfile,
+    /// Whether the key in the credential store passed a TMDB check when it was
+    /// saved. `None` for settings written before keys were checked, whose key
+    /// has been in use all along.
+    #[serde(default)]
+    tmdb_key_validated: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Default)]
 struct SettingsPayload {
     log_path: Option<String>,
     cache_path: Option<String>,
+    clean_profile: goo::CleanProfile,
+    /// A key that TMDB accepted when it was saved is in the credential store.
     tmdb_key_present: bool,
+    path_warning: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Default)]
 struct SettingsInput {
     log_path: Option<String>,
     cache_path: Option<String>,
+    #[serde(default)]
+    clean_profile: goo::CleanProfile,
     tmdb_api_key: Option<String>,
 }
 
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum RevealTarget {
+    Log,
+    Cache,
+    Config,
+}
+
+/// The first page (no `offset`, or 0) always re-reads the log; later pages
+/// reuse that load while the log and cache paths stay the same.
 #[tauri::command]
 fn load_history(
+    snapshot: tauri::State<'_, HistorySnapshot>,
     log_path: Option<String>,
     cache_path: Option<String>,
     tmdb_api_key: Option<String>,
+    offset: Option<usize>,
+    limit: Option<usize>,
 ) -> Result<HistoryPayload, String> {
     let settings = read_settings();
-    let log_path = resolve_log_path(log_path.or(settings.log_path))?;
+    let (log_path, path_warning) = resolve_log_path_checked(log_path.or(settings.log_path));
+    let cache_path = cache_path.or(settings.cache_path);
+    let offset = offset.unwrap_or(0);
+
+    let mut loaded = snapshot.0.lock().map_err(|err| err.to_string())?;
+    let reusable = loaded
+        .as_ref()
+        .is_some_and(|loaded| loaded.log_path == log_path && loaded.cache_path == cache_path);
+    if offset == 0 || !reusable {
+        let api_key = tmdb_api_key
+            .and_then(normalize_key)
+            .or_else(read_tmdb_key);
+        let poster_dir = poster_dir();
+        let request = goo::app::HistoryRequest {
+            cache_path: cache_path.as_deref().map(Path::new),
+            tmdb_api_key: api_key.as_deref(),
+            profile: settings.clean_profile,
+            poster_dir: Some(&poster_dir),
+            ..goo::app::HistoryRequest::new(&log_path)
+        };
+        let mut history = goo::app::enriched_history(request).map_err(|err| err.to_string())?;
+        goo::app::sort_newest_first(&mut history.entries);
+        *loaded = Some(LoadedHistory {
+            log_path,
+            cache_path,
+            entries: history.entries,
+            cache_warning: history.cache_warning,
+            cache_status: history.cache_status,
+            path_warning,
+            overrides_warning: history.overrides_warning,
+            resolved_log_path: history.log_path,
+            log_existed: history.log_existed,
+            resolved_cache_path: history.cache_path,
+            cache_existed: history.cache_existed,
+        });
+    }
+    let Some(loaded) = loaded.as_ref() else {
+        return Err("history is not loaded".to_string());
+    };
+
+    Ok(HistoryPayload {
+        entries: goo::app::history_page(&loaded.entries, offset, limit).to_vec(),
+        total: loaded.entries.len(),
+        cache_warning: loaded.cache_warning.clone(),
+        cache_status: loaded.cache_status.clone(),
+        path_warning: loaded.path_warning.clone(),
+        overrides_warning: loaded.overrides_warning.clone(),
+        resolved_log_path: loaded.resolved_log_path.display().to_string(),
+        log_existed: loaded.log_existed,
+        resolved_cache_path: loaded.resolved_cache_path.display().to_string(),
+        cache_existed: loaded.cache_existed,
+    })
+}
+
+#[tauri::command]
+fn load_dashboard(
+    log_path: Option<String>,
+    cache_path: Option<String>,
+    tmdb_api_key: Option<String>,
+) -> Result<DashboardPayload, String> {
+    let settings = read_settings();
+    let (log_path, path_warning) = resolve_log_path_checked(log_path.or(settings.log_path));
     let cache_path = cache_path.or(settings.cache_path);
     let api_key = tmdb_api_key
         .and_then(normalize_key)
         .or_else(read_tmdb_key);
 
-    let cache_path = cache_path.as_deref().map(Path::new);
-    let api_key = api_key.as_deref();
-    let history =
-        goo::app::load_enriched_history(&log_path, cache_path, api_key).map_err(|err| err.to_string())?;
+    let request = goo::app::HistoryRequest {
+        cache_path: cache_path.as_deref().map(Path::new),
+        tmdb_api_key: api_key.as_deref(),
+        profile: settings.clean_profile,
+        ..goo::app::HistoryRequest::new(&log_path)
+    };
+    let (history, stats) =
+        goo::app::enriched_history_with_stats(request).map_err(|err| err.to_string())?;
 
-    Ok(HistoryPayload {
+    Ok(DashboardPayload {
         entries: history.entries,
         cache_warning: history.cache_warning,
+        cache_status: history.cache_status,
+        path_warning,
+        overrides_warning: history.overrides_warning,
+        stats,
     })
 }
 
 #[tauri::command]
 fn load_settings() -> Result<SettingsPayload, String> {
     let settings = read_settings();
-    let tmdb_key_present = read_tmdb_key().is_some();
+    // Only a key saved unchecked is held back; older installs predate the check.
+    let tmdb_key_present = settings.tmdb_key_validated != Some(false) && read_tmdb_key().is_some();
+    let config_warning = goo::app::config_location().warning;
+    let log_warning = goo::app::resolve_log_path(settings.log_path.as_deref()).warning;
     Ok(SettingsPayload {
         log_path: settings.log_path,
         cache_path: settings.cache_path,
+        clean_profile: settings.clean_profile,
         tmdb_key_present,
+        path_warning: config_warning.or(log_warning),
     })
 }
 
+/// Checks a new TMDB key with one test request before storing it, so a typo
+/// fails here instead of as an empty history later. `skip_validation` stores
+/// it unchecked, e.g. when offline.
 #[tauri::command]
-fn save_settings(settings: SettingsInput) -> Result<(), String> {
+fn save_settings(settings: SettingsInput, skip_validation: Option<bool>) -> Result<(), String> {
+    let key = settings.tmdb_api_key.and_then(normalize_key);
+    let validate = !skip_validation.unwrap_or(false);
+    if let (Some(key), true) = (&key, validate) {
+        goo::tmdb::TmdbClient::new(key.as_str())
+            .validate_key()
+            .map_err(|err| format!("TMDB key not saved: {err}"))?;
+    }
+
     let stored = StoredSettings {
         log_path: settings.log_path,
         cache_path: settings.cache_path,
+        clean_profile: settings.clean_profile,
+        tmdb_key_validated: match key {
+            Some(_) => Some(validate),
+            None => read_settings().tmdb_key_validated,
+        },
     };
     write_settings(&stored)?;
-    if let Some(key) = settings.tmdb_api_key.and_then(normalize_key) {
+    if let Some(key) = key {
         store_tmdb_key(&key)?;
     }
     Ok(())
@@ -80,7 +238,15 @@ fn save_settings(settings: SettingsInput) -> Result<(), String> {
 
 #[tauri::command]
 fn clear_tmdb_key() -> Result<(), String> {
-    delete_tmdb_key()
+    delete_tmdb_key()?;
+    let settings = read_settings();
+    if settings.tmdb_key_validated != Some(false) {
+        write_settings(&StoredSettings {
+            tmdb_key_validated: Some(false),
+            ..settings
+        })?;
+    }
+    Ok(())
 }
 
 #[tauri::command]
@@ -98,25 +264,125 @@ fn delete_entry(
 ) -> Result<(), String> {
     let settings = read_settings();
     let log_path = resolve_log_path(log_path.or(settings.log_path))?;
-    delete_log_entries(&log_path, &cleaned_title, release_year)
+    goo::app::delete_log_entries(&log_path, &cleaned_title, release_year, settings.clean_profile)
+        .map(|_| ())
+        .map_err(|err| err.to_string())
 }
 
-fn resolve_log_path(arg: Option<String>) -> Result<PathBuf, String> {
-    if let Some(value) = arg {
-        return Ok(PathBuf::from(value));
+/// Pins TMDB movie `tmdb_id` as the match for this title, then drops the
+/// loaded history so the next `load_history` shows the correction.
+#[tauri::command]
+fn rematch_entry(
+    snapshot: tauri::State<'_, HistorySnapshot>,
+    log_path: Option<String>,
+    cache_path: Option<String>,
+    cleaned_title: String,
+    release_year: Option<i32>,
+    tmdb_id: u32,
+) -> Result<goo::tmdb::TmdbMovie, String> {
+    let settings = read_settings();
+    let log_path = resolve_log_path(log_path.or(settings.log_path))?;
+    let cache_path = cache_path.or(settings.cache_path);
+    let api_key = read_tmdb_key();
+    let movie = goo::app::rematch_entry(
+        &log_path,
+        cache_path.as_deref().map(Path::new),
+        api_key.as_deref(),
+        &cleaned_title,
+        release_year,
+        tmdb_id,
+    )
+    .map_err(|err| err.to_string())?;
+    if let Ok(mut loaded) = snapshot.0.lock() {
+        *loaded = None;
     }
+    Ok(movie)
+}
 
-    goo::app::default_log_path().ok_or_else(|| {
-        "Log path not found. Set GOO_LOG_PATH or pass a path.".to_string()
-    })
+/// Forgets cached "no match" results and enriches the history again, so
+/// titles TMDB has since added (or that were fixed in the log) get matched.
+/// Returns the first page, like `load_history`.
+#[tauri::command]
+fn retry_unmatched(
+    snapshot: tauri::State<'_, HistorySnapshot>,
+    log_path: Option<String>,
+    cache_path: Option<String>,
+    tmdb_api_key: Option<String>,
+    limit: Option<usize>,
+) -> Result<HistoryPayload, String> {
+    let settings = read_settings();
+    let resolved_log = resolve_log_path(log_path.clone().or(settings.log_path))?;
+    let resolved_cache = cache_path.clone().or(settings.cache_path);
+    goo::app::clear_cache_misses(&resolved_log, resolved_cache.as_deref().map(Path::new))
+        .map_err(|err| err.to_string())?;
+    load_history(snapshot, log_path, cache_path, tmdb_api_key, Some(0), limit)
 }
 
-fn read_settings() -> StoredSettings {
-    let Some(path) = settings_path() else {
-        return StoredSettings::default();
+/// Opens a TMDB page in the system browser; links inside the webview can't.
+#[tauri::command]
+fn open_tmdb(app: tauri::AppHandle, tmdb_url: String) -> Result<(), String> {
+    if !tmdb_url.starts_with("https://www.themoviedb.org/") {
+        return Err(format!("Not a TMDB link: {tmdb_url}"));
+    }
+    app.opener()
+        .open_url(tmdb_url, None::<&str>)
+        .map_err(|err| err.to_string())
+}
+
+/// Plays a history entry again from the path it was logged with.
+#[tauri::command]
+fn play_in_vlc(source_path: String) -> Result<(), String> {
+    if source_path.trim().is_empty() {
+        return Err("This entry has no file path to play".to_string());
+    }
+    goo::app::play_in_vlc(&source_path).map_err(|err| err.to_string())
+}
+
+/// Opens the folder holding the log, cache or settings file, creating it when
+/// nothing has been written there yet, and returns the file's path so the UI
+/// can show it.
+#[tauri::command]
+fn reveal_path(
+    app: tauri::AppHandle,
+    which: RevealTarget,
+    log_path: Option<String>,
+    cache_path: Option<String>,
+) -> Result<String, String> {
+    let settings = read_settings();
+    let path = match which {
+        RevealTarget::Log => resolve_log_path(log_path.or(settings.log_path))?,
+        RevealTarget::Cache => {
+            let log_path = resolve_log_path(log_path.or(settings.log_path))?;
+            let cache_path = cache_path.or(settings.cache_path);
+            goo::app::resolve_cache_path(cache_path.as_deref().map(Path::new), &log_path)
+        }
+        RevealTarget::Config => settings_path(),
     };
 
-    let Ok(content) = fs::read_to_string(path) else {
+    let folder = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .ok_or_else(|| format!("No containing folder for {}", path.display()))?;
+    fs::create_dir_all(folder).map_err(|err| err.to_string())?;
+    app.opener()
+        .open_path(folder.to_string_lossy(), None::<&str>)
+        .map_err(|err| err.to_string())?;
+    Ok(path.display().to_string())
+}
+
+fn resolve_log_path(arg: Option<String>) -> Result<PathBuf, String> {
+    Ok(resolve_log_path_checked(arg).0)
+}
+
+/// The log path, plus a warning when the default had to fall back because
+/// HOME/APPDATA is unset.
+fn resolve_log_path_checked(arg: Option<String>) -> (PathBuf, Option<String>) {
+    let resolved = goo::app::resolve_log_path(arg.as_deref());
+    (resolved.path, resolved.warning)
+}
+
+fn read_settings() -> StoredSettings {
+    let Ok(content) = fs::read_to_string(settings_path()) else {
         return StoredSettings::default();
     };
 
@@ -124,9 +390,7 @@ fn read_settings() -> StoredSettings {
 }
 
 fn write_settings(settings: &StoredSettings) -> Result<(), String> {
-    let Some(path) = settings_path() else {
-        return Err("Settings path not available".to_string());
-    };
+    let path = settings_path();
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent).map_err(|err| err.to_string())?;
     }
@@ -134,28 +398,18 @@ fn write_settings(settings: &StoredSettings) -> Result<(), String> {
     fs::write(path, payload).map_err(|err| err.to_string())
 }
 
-fn settings_path() -> Option<PathBuf> {
-    let base = config_base_dir()?;
-    Some(base.join("settings.json"))
+fn settings_path() -> PathBuf {
+    config_base_dir().join("settings.json")
 }
 
-fn config_base_dir() -> Option<PathBuf> {
-    #[cfg(target_os = "windows")]
-    {
-        std::env::var_os("APPDATA").map(|base| PathBuf::from(base).join("goo"))
-    }
-    #[cfg(target_os = "macos")]
-    {
-        std::env::var_os("HOME")
-            .map(|base| PathBuf::from(base).join("Library").join("Application Support").join("goo"))
-    }
-    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
-    {
-        if let Some(base) = std::env::var_os("XDG_CONFIG_HOME") {
-            return Some(PathBuf::from(base).join("goo"));
-        }
-        std::env::var_os("HOME").map(|base| PathBuf::from(base).join(".config").join("goo"))
-    }
+fn config_base_dir() -> PathBuf {
+    goo::app::config_location().path
+}
+
+/// Where downloaded posters are kept; the asset protocol scope in
+/// tauri.conf.json lets the webview load them from here.
+fn poster_dir() -> PathBuf {
+    config_base_dir().join("posters")
 }
 
 fn normalize_key(value: String) -> Option<String> {
@@ -167,40 +421,49 @@ fn normalize_key(value: String) -> Option<String> {
     }
 }
 
-#[cfg(target_os = "windows")]
+/// The OS credential store entry for the TMDB key: Credential Manager on
+/// Windows, Keychain on macOS, Secret Service on Linux.
+fn tmdb_key_entry() -> Option<keyring::Entry> {
+    keyring::Entry::new("goo", "tmdb_api_key").ok()
+}
+
+/// A platform failure means there is no usable credential store (e.g. no
+/// Secret Service running), which callers treat like an empty one.
+fn keyring_unavailable(err: &keyring::Error) -> bool {
+    matches!(err, keyring::Error::PlatformFailure(_))
+}
+
 fn read_tmdb_key() -> Option<String> {
-    let entry = keyring::Entry::new("goo", "tmdb_api_key").ok()?;
+    let entry = tmdb_key_entry()?;
     match entry.get_password() {
         Ok(value) => normalize_key(value),
         Err(_) => None,
     }
 }
 
-#[cfg(not(target_os = "windows"))]
-fn read_tmdb_key() -> Option<String> {
-    None
-}
-
-#[cfg(target_os = "windows")]
 fn store_tmdb_key(value: &str) -> Result<(), String> {
-    let entry = keyring::Entry::new("goo", "tmdb_api_key").map_err(|err| err.to_string())?;
-    entry.set_password(value).map_err(|err| err.to_string())
-}
-
-#[cfg(not(target_os = "windows"))]
-fn store_tmdb_key(_value: &str) -> Result<(), String> {
-    Ok(())
-}
-
-#[cfg(target_os = "windows")]
-fn delete_tmdb_key() -> Result<(), String> {
-    let entry = keyring::Entry::new("goo", "tmdb_api_key").map_err(|err| err.to_string())?;
-    entry.delete_password().map_err(|err| err.to_string())
+    let Some(entry) = tmdb_key_entry() else {
+        return Ok(());
+    };
+    match entry.set_password(value) {
+        Ok(()) => Ok(()),
+        Err(err) if keyring_unavailable(&err) => {
+            eprintln!("No credential store available, TMDB key not saved: {err}");
+            Ok(())
+        }
+        Err(err) => Err(err.to_string()),
+    }
 }
 
-#[cfg(not(target_os = "windows"))]
 fn delete_tmdb_key() -> Result<(), String> {
-    Ok(())
+    let Some(entry) = tmdb_key_entry() else {
+        return Ok(());
+    };
+    match entry.delete_password() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(err) if keyring_unavailable(&err) => Ok(()),
+        Err(err) => Err(err.to_string()),
+    }
 }
 
 fn install_vlc_logger() -> Result<(), String> {
@@ -316,6 +579,8 @@ fn is_setting_line(line: &str, key: &str) -> bool {
 
 fn main() {
     tauri::Builder::default()
+        .plugin(tauri_plugin_opener::init())
+        .manage(HistorySnapshot::default())
         .setup(|_| {
             if let Err(err) = install_vlc_logger() {
                 eprintln!("Failed to install VLC logger: {err}");
@@ -324,11 +589,17 @@ fn main() {
         })
         .invoke_handler(tauri::generate_handler![
             load_history,
+            load_dashboard,
             load_settings,
             save_settings,
             clear_tmdb_key,
             delete_log,
-            delete_entry
+            delete_entry,
+            rematch_entry,
+            retry_unmatched,
+            open_tmdb,
+            play_in_vlc,
+            reveal_path
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -341,50 +612,3 @@ fn delete_log_file(path: &Path) -> Result<(), String> {
         Err(err) => Err(err.to_string()),
     }
 }
-
-fn delete_log_entries(
-    path: &Path,
-    cleaned_title: &str,
-    release_year: Option<i32>,
-) -> Result<(), String> {
-    let target = cleaned_title.trim();
-    if target.is_empty() {
-        return Ok(());
-    }
-
-    let content = match fs::read_to_string(path) {
-        Ok(content) => content,
-        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
-        Err(err) => return Err(err.to_string()),
-    };
-
-    let target_normalized = target.to_lowercase();
-    let mut kept = Vec::new();
-    let mut removed_any = false;
-
-    for line in content.lines() {
-        let should_remove = goo::parse_log_line(line)
-            .map(|entry| {
-                entry.cleaned_title.trim().to_lowercase() == target_normalized
-                    && entry.release_year == release_year
-            })
-            .unwrap_or(false);
-
-        if should_remove {
-            removed_any = true;
-            continue;
-        }
-
-        kept.push(line);
-    }
-
-    if !removed_any {
-        return Ok(());
-    }
-
-    let mut new_content = kept.join("\n");
-    if !new_content.is_empty() {
-        new_content.push('\n');
-    }
-    fs::write(path, new_content).map_err(|err| err.to_string())
-}
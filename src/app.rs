@@ -1,11 +1,38 @@
-use crate::enrich::{enrich_entries, EnrichedEntry, MovieCache};
-use crate::tmdb::{TmdbClient, TmdbError};
-use crate::read_watch_log;
+use crate::enrich::{
+    attach_details, attach_directors, attach_imdb_ids, attach_overviews, download_posters,
+    enrich_entries_parallel, enrich_entries_routed, refine_entries, CacheStatus, EnrichOptions,
+    EnrichedEntry, ForceTvList, MovieCache, TitleOverrides,
+};
+use crate::import::{trakt_log_lines, TraktImport};
+use crate::{
+    merge_parts, parse_log_line_with, parse_timestamp, read_watch_log_with, CleanProfile, WatchEntry,
+};
+use crate::stats::WatchStats;
+use crate::tmdb::{TmdbClient, TmdbError, TmdbMovie};
+use std::collections::BTreeMap;
+use std::ffi::{OsStr, OsString};
 use std::path::{Path, PathBuf};
+use std::process::Command;
 
 #[derive(Debug)]
 pub enum AppError {
+    /// Never returned: every lookup falls back to a default log path.
+    #[deprecated(note = "the log path always resolves; see `default_log_location`")]
     MissingLogPath,
+    /// TMDB has no movie with this id.
+    UnknownMovie(u32),
+    /// No VLC executable where goo looked for one.
+    VlcNotFound(PathBuf),
+    /// A date bound that is neither `YYYY-MM-DD` nor an ISO 8601 timestamp.
+    InvalidDate(String),
+    /// The cache can't be written, found before any lookups were made; see
+    /// [`UnwritableCache::Fail`].
+    CacheUnwritable { path: PathBuf, reason: String },
+    /// The cache isn't valid JSON, so it was left alone rather than saved over;
+    /// `goo cache repair` may recover it.
+    CacheCorrupted(PathBuf),
+    /// A file to import that isn't in the expected export format.
+    InvalidExport { path: PathBuf, reason: String },
     Io(std::io::Error),
     Tmdb(TmdbError),
 }
@@ -13,8 +40,77 @@ pub enum AppError {
 #[derive(Debug, Clone)]
 pub struct EnrichedHistory {
     pub entries: Vec<EnrichedEntry>,
+    /// The log that was read, after defaults were applied.
+    pub log_path: PathBuf,
+    /// Whether the log existed when it was read; a missing log reads as empty.
+    pub log_existed: bool,
     pub cache_path: PathBuf,
+    /// Whether there was a cache to load, rather than starting a new one.
+    pub cache_existed: bool,
+    /// Whether the cache loaded cleanly and was saved; see [`CacheStatus`].
+    pub cache_status: CacheStatus,
+    /// `cache_status` as a message, `None` when it is ok. Kept for callers
+    /// written before [`CacheStatus`], such as the desktop UI's banner, until
+    /// they read `cache_status` instead.
     pub cache_warning: Option<String>,
+    /// Why the title overrides next to the log were skipped, if they were.
+    pub overrides_warning: Option<String>,
+}
+
+/// Where to read history from and how to reach TMDB.
+#[derive(Debug, Clone, Copy)]
+pub struct HistoryRequest<'a> {
+    pub log_path: &'a Path,
+    pub cache_path: Option<&'a Path>,
+    pub tmdb_api_key: Option<&'a str>,
+    pub profile: CleanProfile,
+    /// Attach each match's full TMDB search result JSON; see
+    /// [`TmdbClient::with_raw_results`].
+    pub include_raw: bool,
+    /// Report rate-limit waits and retries on stderr, so slow runs don't look hung.
+    pub verbose: bool,
+    /// Leave out watches abandoned early; the default keeps everything.
+    pub min_watched: WatchThreshold,
+    /// What to do when the cache turns out not to be writable.
+    pub on_unwritable_cache: UnwritableCache,
+    /// Keep posters in this folder and point entries at the local files; see
+    /// [`download_posters`]. Only [`enriched_history`] downloads them.
+    pub poster_dir: Option<&'a Path>,
+    /// Look uncached titles up on this many threads; see
+    /// [`enrich_entries_parallel`]. `1` looks them up one at a time.
+    pub concurrency: usize,
+}
+
+impl<'a> HistoryRequest<'a> {
+    /// The log at `log_path` with everything else at its default: the cache
+    /// next to the log, the key from the environment and the standard profile.
+    /// Set the fields that differ with struct update syntax.
+    pub fn new(log_path: &'a Path) -> Self {
+        Self {
+            log_path,
+            cache_path: None,
+            tmdb_api_key: None,
+            profile: CleanProfile::default(),
+            include_raw: false,
+            verbose: false,
+            min_watched: WatchThreshold::default(),
+            on_unwritable_cache: UnwritableCache::default(),
+            poster_dir: None,
+            concurrency: 1,
+        }
+    }
+}
+
+/// What to do when the cache can't be written, checked before enrichment
+/// spends any API calls. Either way the run's lookups would otherwise be lost.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnwritableCache {
+    /// Enrich anyway, keeping lookups in memory, and report
+    /// [`CacheStatus::Unwritable`].
+    #[default]
+    InMemory,
+    /// Stop with [`AppError::CacheUnwritable`] before any lookups.
+    Fail,
 }
 
 pub fn load_enriched_history(
@@ -22,53 +118,690 @@ pub fn load_enriched_history(
     cache_path: Option<&Path>,
     tmdb_api_key: Option<&str>,
 ) -> Result<EnrichedHistory, AppError> {
-    let cache_path = cache_path
-        .map(PathBuf::from)
-        .unwrap_or_else(|| default_cache_path(log_path));
-    let client = if let Some(key) = tmdb_api_key {
-        TmdbClient::new(key)
-    } else {
-        TmdbClient::from_env()?
+    enriched_history(HistoryRequest {
+        cache_path,
+        tmdb_api_key,
+        ..HistoryRequest::new(log_path)
+    })
+}
+
+/// [`load_enriched_history`] for a full [`HistoryRequest`]. Matched films
+/// also get their IMDb link and, when TMDB has none in the client's language,
+/// an overview from its fallback chain; see [`attach_imdb_ids`] and
+/// [`attach_overviews`].
+pub fn enriched_history(request: HistoryRequest<'_>) -> Result<EnrichedHistory, AppError> {
+    let client = request_client(&request)?;
+    let passes = HistoryPasses {
+        profile: request.profile,
+        min_watched: request.min_watched,
+        on_unwritable_cache: request.on_unwritable_cache,
+        concurrency: request.concurrency,
+        overviews: true,
+        imdb_ids: true,
+        ..HistoryPasses::default()
+    };
+    let mut history =
+        enrich_history(request.log_path, request.cache_path, &client, passes, |_| {})?;
+    if let Some(dir) = request.poster_dir {
+        // Local posters only save refetching; when the folder can't be made,
+        // entries keep their remote urls.
+        let _ = download_posters(&mut history.entries, &client, dir);
+    }
+    Ok(history)
+}
+
+/// Enriches the history and tallies [`WatchStats`] over it in the same pass.
+/// The stats describe exactly the entries returned.
+pub fn enriched_history_with_stats(
+    request: HistoryRequest<'_>,
+) -> Result<(EnrichedHistory, WatchStats), AppError> {
+    let client = request_client(&request)?;
+    history_with_stats(request, &client)
+}
+
+fn history_with_stats(
+    request: HistoryRequest<'_>,
+    client: &TmdbClient,
+) -> Result<(EnrichedHistory, WatchStats), AppError> {
+    let mut stats = WatchStats::default();
+    let passes = HistoryPasses {
+        profile: request.profile,
+        min_watched: request.min_watched,
+        on_unwritable_cache: request.on_unwritable_cache,
+        concurrency: request.concurrency,
+        ..HistoryPasses::default()
+    };
+    let history = enrich_history(
+        request.log_path,
+        request.cache_path,
+        client,
+        passes,
+        |entry| stats.record(&entry.cleaned_title, entry.release_year),
+    )?;
+    Ok((history, stats))
+}
+
+/// Like [`load_enriched_history`], but first re-searches cached matches scoring
+/// below `threshold` so only the shaky ones spend API calls.
+pub fn load_refined_history(
+    log_path: &Path,
+    cache_path: Option<&Path>,
+    tmdb_api_key: Option<&str>,
+    threshold: f32,
+) -> Result<EnrichedHistory, AppError> {
+    let request = HistoryRequest {
+        cache_path,
+        tmdb_api_key,
+        ..HistoryRequest::new(log_path)
     };
-    let entries = read_watch_log(log_path)?;
+    refined_history(request, threshold)
+}
+
+/// [`load_refined_history`] for a full [`HistoryRequest`], with IMDb links
+/// and fallback overviews like [`enriched_history`].
+pub fn refined_history(
+    request: HistoryRequest<'_>,
+    threshold: f32,
+) -> Result<EnrichedHistory, AppError> {
+    let passes = HistoryPasses {
+        profile: request.profile,
+        min_watched: request.min_watched,
+        on_unwritable_cache: request.on_unwritable_cache,
+        concurrency: request.concurrency,
+        overviews: true,
+        imdb_ids: true,
+        refine_below: Some(threshold),
+        ..HistoryPasses::default()
+    };
+    let client = request_client(&request)?;
+    enrich_history(request.log_path, request.cache_path, &client, passes, |_| {})
+}
+
+/// Like [`load_enriched_history`], but also fetches runtime and genres per matched movie.
+pub fn load_detailed_history(
+    log_path: &Path,
+    cache_path: Option<&Path>,
+    tmdb_api_key: Option<&str>,
+) -> Result<EnrichedHistory, AppError> {
+    let passes = HistoryPasses {
+        details: true,
+        ..HistoryPasses::default()
+    };
+    let client = tmdb_client(tmdb_api_key)?;
+    enrich_history(log_path, cache_path, &client, passes, |_| {})
+}
+
+/// Like [`load_enriched_history`], but also fetches each matched film's
+/// credits to fill in its directors. That's an extra request per film, so
+/// only callers that want director stats should ask for it.
+pub fn load_credited_history(
+    log_path: &Path,
+    cache_path: Option<&Path>,
+    tmdb_api_key: Option<&str>,
+) -> Result<EnrichedHistory, AppError> {
+    let passes = HistoryPasses {
+        credits: true,
+        ..HistoryPasses::default()
+    };
+    let client = tmdb_client(tmdb_api_key)?;
+    enrich_history(log_path, cache_path, &client, passes, |_| {})
+}
+
+/// Like [`load_enriched_history`], but only for watches between `from` and
+/// `to` (inclusive; either may be left open). Bounds are `YYYY-MM-DD` dates,
+/// where a `to` date covers that whole day, or full ISO 8601 timestamps.
+/// Entries are filtered before enrichment, so out-of-range titles cost no
+/// TMDB requests. Once a bound is set, undated entries are left out.
+pub fn load_enriched_history_range(
+    log_path: &Path,
+    cache_path: Option<&Path>,
+    tmdb_api_key: Option<&str>,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<EnrichedHistory, AppError> {
+    let range = WatchRange::parse(from, to)?;
+    let client = tmdb_client(tmdb_api_key)?;
+    range_history(log_path, cache_path, &client, range)
+}
+
+fn range_history(
+    log_path: &Path,
+    cache_path: Option<&Path>,
+    client: &TmdbClient,
+    range: WatchRange,
+) -> Result<EnrichedHistory, AppError> {
+    let passes = HistoryPasses {
+        range,
+        ..HistoryPasses::default()
+    };
+    enrich_history(log_path, cache_path, client, passes, |_| {})
+}
+
+/// An inclusive span of watch times in unix seconds; `None` leaves that end open.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WatchRange {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+impl WatchRange {
+    /// Parses the bounds of [`load_enriched_history_range`].
+    pub fn parse(from: Option<&str>, to: Option<&str>) -> Result<Self, AppError> {
+        let bound = |value: &str, end_of_day: bool| {
+            let value = value.trim();
+            let parsed = parse_timestamp(value).ok_or_else(|| AppError::InvalidDate(value.into()))?;
+            let date_only = value.len() == "YYYY-MM-DD".len();
+            let extra = if end_of_day && date_only { 86_399 } else { 0 };
+            Ok::<_, AppError>(parsed.to_unix() + extra)
+        };
+        Ok(Self {
+            from: from.map(|value| bound(value, false)).transpose()?,
+            to: to.map(|value| bound(value, true)).transpose()?,
+        })
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.from.is_none() && self.to.is_none()
+    }
+
+    /// Whether `entry` was watched inside the range. With any bound set,
+    /// entries without a readable timestamp are outside it.
+    pub fn contains(&self, entry: &WatchEntry) -> bool {
+        if self.is_open() {
+            return true;
+        }
+        let Some(at) = entry.watched_at.as_deref().and_then(parse_timestamp) else {
+            return false;
+        };
+        let at = at.to_unix();
+        self.from.is_none_or(|from| at >= from) && self.to.is_none_or(|to| at <= to)
+    }
+}
+
+/// How much of a film must have played for a watch to count, for leaving out
+/// ones abandoned after a few minutes. Only JSON log lines record playback,
+/// so entries without a position or duration always count. The default
+/// keeps everything.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct WatchThreshold {
+    /// Fraction of the film, from 0 to 1, that must have played.
+    pub min_fraction: f32,
+    /// Seconds that must have played; needs both position and duration.
+    pub min_seconds: u32,
+}
+
+impl WatchThreshold {
+    pub fn keeps(&self, entry: &WatchEntry) -> bool {
+        let Some(position) = entry.position else {
+            return true;
+        };
+        if position < self.min_fraction {
+            return false;
+        }
+        entry
+            .duration
+            .is_none_or(|duration| position * duration as f32 >= self.min_seconds as f32)
+    }
+}
+
+/// How titles are cleaned, plus optional work layered on top of plain enrichment.
+#[derive(Debug, Clone, Copy, Default)]
+struct HistoryPasses {
+    profile: CleanProfile,
+    /// Only enrich watches inside this range.
+    range: WatchRange,
+    min_watched: WatchThreshold,
+    on_unwritable_cache: UnwritableCache,
+    /// Threads for uncached lookups; `0` and `1` both mean one at a time.
+    concurrency: usize,
+    details: bool,
+    credits: bool,
+    /// Fill blank overviews from the client's language fallback chain.
+    overviews: bool,
+    /// Link matched films to IMDb.
+    imdb_ids: bool,
+    refine_below: Option<f32>,
+}
+
+/// The client configured from the environment, with `tmdb_api_key` in place
+/// of the credentials there when one is passed.
+fn tmdb_client(tmdb_api_key: Option<&str>) -> Result<TmdbClient, TmdbError> {
+    let client = match tmdb_api_key {
+        Some(key) => TmdbClient::from_env_with_key(key)?,
+        None => TmdbClient::from_env()?,
+    };
+    if let Some(warning) = client.proxy_warning() {
+        eprintln!("Warning: {warning}");
+    }
+    Ok(client)
+}
+
+fn request_client(request: &HistoryRequest<'_>) -> Result<TmdbClient, TmdbError> {
+    let client = tmdb_client(request.tmdb_api_key)?.with_raw_results(request.include_raw);
+    if !request.verbose {
+        return Ok(client);
+    }
+    Ok(client.with_throttle_log(|event| eprintln!("{event}")))
+}
 
-    let mut cache = MovieCache::load(&cache_path);
-    let enriched = enrich_entries(entries, &client, &mut cache)?;
-    let cache_warning = cache
-        .save(&cache_path)
-        .err()
-        .map(|error| error.to_string());
+fn enrich_history(
+    log_path: &Path,
+    cache_path: Option<&Path>,
+    client: &TmdbClient,
+    passes: HistoryPasses,
+    on_entry: impl FnMut(&EnrichedEntry),
+) -> Result<EnrichedHistory, AppError> {
+    let cache_path = resolve_cache_path(cache_path, log_path);
+    let log_existed = log_path.exists();
+    let cache_existed = cache_path.exists();
+    let unwritable = check_cache_writable(&cache_path).err();
+    if let (Some(error), UnwritableCache::Fail) = (&unwritable, passes.on_unwritable_cache) {
+        return Err(AppError::CacheUnwritable {
+            path: cache_path,
+            reason: error.to_string(),
+        });
+    }
+    let mut entries = merge_parts(read_watch_log_with(log_path, &passes.profile.cleaner())?);
+    entries.retain(|entry| passes.range.contains(entry) && passes.min_watched.keeps(entry));
+
+    let force_tv = ForceTvList::load(&default_force_tv_path(log_path))?;
+    // A hand-edited file with a typo shouldn't stop the whole history loading.
+    let (overrides, overrides_warning) =
+        TitleOverrides::load_checked(&default_overrides_path(log_path));
+
+    let (mut cache, load_status) = MovieCache::load_with_status(&cache_path);
+    if let Some(threshold) = passes.refine_below {
+        refine_entries(&entries, client, &mut cache, &overrides, threshold)?;
+    }
+    let options = EnrichOptions::default().with_overrides(overrides);
+    let mut enriched = if passes.concurrency > 1 {
+        let enriched = enrich_entries_parallel(
+            entries,
+            client,
+            &mut cache,
+            &force_tv,
+            &options,
+            passes.concurrency,
+        )?;
+        enriched.iter().for_each(on_entry);
+        enriched
+    } else {
+        enrich_entries_routed(entries, client, &mut cache, &force_tv, &options, on_entry)?
+    };
+    if passes.details {
+        attach_details(&mut enriched, client, &mut cache)?;
+    }
+    if passes.credits {
+        attach_directors(&mut enriched, client, &mut cache)?;
+    }
+    if passes.overviews {
+        attach_overviews(&mut enriched, client, &mut cache)?;
+    }
+    if passes.imdb_ids {
+        attach_imdb_ids(&mut enriched, client, &mut cache)?;
+    }
+    let save_status = match unwritable {
+        // Already known to fail, so don't try to save.
+        Some(error) => CacheStatus::Unwritable {
+            path: cache_path.clone(),
+            reason: error.to_string(),
+        },
+        // Saving would replace what `goo cache repair` could still recover.
+        None if matches!(load_status, CacheStatus::LoadCorrupted { .. }) => CacheStatus::Ok,
+        None => match cache.save(&cache_path) {
+            Ok(()) => CacheStatus::Ok,
+            Err(error) => CacheStatus::SaveFailed {
+                path: cache_path.clone(),
+                reason: error.to_string(),
+            },
+        },
+    };
+    // A load problem explains any save failure that follows it.
+    let cache_status = if load_status.is_ok() {
+        save_status
+    } else {
+        load_status
+    };
 
     Ok(EnrichedHistory {
         entries: enriched,
+        log_path: log_path.to_path_buf(),
+        log_existed,
         cache_path,
-        cache_warning,
+        cache_existed,
+        cache_warning: cache_status.warning(),
+        cache_status,
+        overrides_warning,
     })
 }
 
+/// Replaces the match for `cleaned_title`/`release_year` with TMDB movie
+/// `tmdb_id`, for when search picked the wrong film. The movie is fetched by
+/// id and pinned in the cache (next to `log_path` unless `cache_path` is
+/// given), so every later load shows it.
+pub fn rematch_entry(
+    log_path: &Path,
+    cache_path: Option<&Path>,
+    tmdb_api_key: Option<&str>,
+    cleaned_title: &str,
+    release_year: Option<i32>,
+    tmdb_id: u32,
+) -> Result<TmdbMovie, AppError> {
+    let client = tmdb_client(tmdb_api_key)?;
+    let movie = client
+        .movie_by_id(tmdb_id)?
+        .ok_or(AppError::UnknownMovie(tmdb_id))?;
+
+    let cache_path = resolve_cache_path(cache_path, log_path);
+    let mut cache = load_cache_to_update(&cache_path)?;
+    cache.pin_match(cleaned_title, release_year, movie.clone());
+    cache.save(&cache_path)?;
+    Ok(movie)
+}
+
+/// Loads the cache at `path` for a change that will be saved back, refusing
+/// one that didn't parse so the save can't overwrite what's left of it.
+fn load_cache_to_update(path: &Path) -> Result<MovieCache, AppError> {
+    match MovieCache::load_with_status(path) {
+        (_, CacheStatus::LoadCorrupted { path }) => Err(AppError::CacheCorrupted(path)),
+        (cache, _) => Ok(cache),
+    }
+}
+
+/// Appends the watches in the Trakt history export at `export_path` to the
+/// log at `log_path`, creating it if needed, so they load like any other
+/// watch. See [`trakt_log_lines`] for how items are written.
+pub fn import_trakt(export_path: &Path, log_path: &Path) -> Result<TraktImport, AppError> {
+    let export = std::fs::read_to_string(export_path)?;
+    let import = trakt_log_lines(&export).map_err(|error| AppError::InvalidExport {
+        path: export_path.to_path_buf(),
+        reason: error.to_string(),
+    })?;
+    if import.lines.is_empty() {
+        return Ok(import);
+    }
+
+    // Don't glue the first imported line onto a last line without a newline.
+    let unterminated = std::fs::read(log_path)
+        .is_ok_and(|log| log.last().is_some_and(|byte| *byte != b'\n'));
+    let mut text = if unterminated { String::from("\n") } else { String::new() };
+    for line in &import.lines {
+        text.push_str(line);
+        text.push('\n');
+    }
+    let mut log = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_path)?;
+    std::io::Write::write_all(&mut log, text.as_bytes())?;
+    Ok(import)
+}
+
+/// Drops every cached "no match" from the cache next to `log_path` (or at
+/// `cache_path`), so the next load searches TMDB for those titles again.
+/// Returns how many misses were cleared.
+pub fn clear_cache_misses(log_path: &Path, cache_path: Option<&Path>) -> Result<usize, AppError> {
+    let cache_path = resolve_cache_path(cache_path, log_path);
+    let mut cache = load_cache_to_update(&cache_path)?;
+    let cleared = cache.clear_misses();
+    if cleared > 0 {
+        cache.save(&cache_path)?;
+    }
+    Ok(cleared)
+}
+
+/// One line of `goo doctor`'s checklist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub name: &'static str,
+    pub passed: bool,
+    /// Enrichment can't work while this check fails.
+    pub critical: bool,
+    /// What was found, or what went wrong.
+    pub detail: String,
+    /// What to do about a failure.
+    pub hint: Option<&'static str>,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: String) -> Self {
+        Self { name, passed: true, critical: false, detail, hint: None }
+    }
+
+    fn fail(name: &'static str, critical: bool, detail: String, hint: &'static str) -> Self {
+        Self { name, passed: false, critical, detail, hint: Some(hint) }
+    }
+}
+
+/// Checks what enrichment depends on: a readable log with entries in it, the
+/// cache, TMDB credentials from the environment, and a test search.
+pub fn diagnose(log_path: &Path, cache_path: Option<&Path>) -> Vec<Check> {
+    diagnose_with(log_path, cache_path, TmdbClient::from_env())
+}
+
+fn diagnose_with(
+    log_path: &Path,
+    cache_path: Option<&Path>,
+    client: Result<TmdbClient, TmdbError>,
+) -> Vec<Check> {
+    let mut checks = Vec::new();
+    let shown = log_path.display().to_string();
+    match std::fs::File::open(log_path) {
+        Ok(_) => {
+            checks.push(Check::pass("Log file", shown));
+            checks.push(match crate::read_watch_log(log_path) {
+                Ok(entries) if !entries.is_empty() => {
+                    Check::pass("Log entries", format!("{} parsed", entries.len()))
+                }
+                Ok(_) => Check::fail(
+                    "Log entries",
+                    false,
+                    "none parsed".to_string(),
+                    "Play something in VLC with the goo extension enabled, or check the log path",
+                ),
+                Err(error) => Check::fail(
+                    "Log entries",
+                    true,
+                    error.to_string(),
+                    "Check the log is a text file goo can read",
+                ),
+            });
+        }
+        Err(error) => checks.push(Check::fail(
+            "Log file",
+            true,
+            format!("{shown}: {error}"),
+            "Set GOO_LOG_PATH or pass the log path",
+        )),
+    }
+
+    let cache_path = resolve_cache_path(cache_path, log_path);
+    checks.push(if cache_path.exists() {
+        match MovieCache::load_with_status(&cache_path).1 {
+            CacheStatus::Ok => Check::pass("Cache", cache_path.display().to_string()),
+            status => Check::fail(
+                "Cache",
+                false,
+                status.to_string(),
+                "Matches will be looked up again until the cache is fixed",
+            ),
+        }
+    } else {
+        Check::pass("Cache", format!("{} (created on first enrich)", cache_path.display()))
+    });
+
+    let client = match client {
+        Ok(client) => {
+            checks.push(Check::pass("TMDB credentials", "set".to_string()));
+            client
+        }
+        Err(error) => {
+            checks.push(Check::fail(
+                "TMDB credentials",
+                true,
+                error.to_string(),
+                "Set TMDB_API_KEY to continue",
+            ));
+            return checks;
+        }
+    };
+    checks.push(match client.search_movie("Dune", Some(2021)) {
+        Ok(results) => {
+            Check::pass("TMDB search", format!("{} results for \"Dune\"", results.len()))
+        }
+        Err(error) => Check::fail(
+            "TMDB search",
+            true,
+            error.to_string(),
+            "Check the key is valid and that api.themoviedb.org is reachable",
+        ),
+    });
+    checks
+}
+
+/// Orders entries newest watch first, the order [`history_page`] slices, so
+/// pages stay put while older history is paged through. Undated entries go
+/// last, in log order.
+pub fn sort_newest_first(entries: &mut [EnrichedEntry]) {
+    entries.sort_by_key(|entry| {
+        let watched = entry.watched_at.as_deref().and_then(parse_timestamp);
+        std::cmp::Reverse(watched.map(|watched| watched.to_unix()))
+    });
+}
+
+/// How `goo` orders the entries it prints.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EntryOrder {
+    /// As they appear in the log.
+    #[default]
+    File,
+    /// By cleaned title, ignoring case.
+    Title,
+    /// By watch time, undated entries last.
+    Date,
+}
+
+impl EntryOrder {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "file" => Some(EntryOrder::File),
+            "title" => Some(EntryOrder::Title),
+            "date" => Some(EntryOrder::Date),
+            _ => None,
+        }
+    }
+}
+
+/// Sorts `entries` stably by `order`, flipped when `reverse` is set. Undated
+/// entries stay last under [`EntryOrder::Date`] either way.
+pub fn sort_entries(entries: &mut [WatchEntry], order: EntryOrder, reverse: bool) {
+    match order {
+        EntryOrder::File => {}
+        EntryOrder::Title => entries.sort_by_cached_key(|entry| entry.cleaned_title.to_lowercase()),
+        EntryOrder::Date => entries.sort_by_key(|entry| {
+            let watched = entry.watched_at.as_deref().and_then(parse_timestamp);
+            let unix = watched.map(|watched| watched.to_unix());
+            (unix.is_none(), if reverse { unix.map(|unix| -unix) } else { unix })
+        }),
+    }
+    if reverse && order != EntryOrder::Date {
+        entries.reverse();
+    }
+}
+
+/// Up to `limit` entries starting at `offset` (all the rest without a limit).
+/// An offset past the end gives an empty page rather than an error.
+pub fn history_page(
+    entries: &[EnrichedEntry],
+    offset: usize,
+    limit: Option<usize>,
+) -> &[EnrichedEntry] {
+    let start = offset.min(entries.len());
+    let end = match limit {
+        Some(limit) => start.saturating_add(limit).min(entries.len()),
+        None => entries.len(),
+    };
+    &entries[start..end]
+}
+
+/// The [`group_by_month`] bucket for entries without a usable timestamp.
+pub const UNKNOWN_MONTH: &str = "Unknown";
+
+/// Groups entries by the UTC month they were watched, keyed `YYYY-MM` so the
+/// map iterates oldest first. Entries whose `watched_at` is missing or doesn't
+/// parse go under [`UNKNOWN_MONTH`], which sorts last. Log order is kept
+/// within each month.
+pub fn group_by_month(entries: Vec<WatchEntry>) -> BTreeMap<String, Vec<WatchEntry>> {
+    let mut months: BTreeMap<String, Vec<WatchEntry>> = BTreeMap::new();
+    for entry in entries {
+        months.entry(month_of(entry.watched_at.as_deref())).or_default().push(entry);
+    }
+    months
+}
+
+/// The `YYYY-MM` [`group_by_month`] files a watch under.
+pub(crate) fn month_of(watched_at: Option<&str>) -> String {
+    match watched_at.and_then(parse_timestamp) {
+        Some(watched) => format!("{:04}-{:02}", watched.year, watched.month),
+        None => UNKNOWN_MONTH.to_string(),
+    }
+}
+
+/// A default location, and why it had to fall back when the usual
+/// environment variable was missing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefaultPath {
+    pub path: PathBuf,
+    pub warning: Option<String>,
+}
+
+/// Where the watch log lives when no path is passed. Always `Some`; see
+/// [`default_log_location`] for the warning when HOME/APPDATA is unset.
+#[deprecated(note = "always `Some`; use `default_log_location`")]
 pub fn default_log_path() -> Option<PathBuf> {
-    if let Some(path) = std::env::var_os("GOO_LOG_PATH") {
+    Some(default_log_location().path)
+}
+
+/// Where the watch log lives when no path is passed, plus a warning when it
+/// fell back to the temp dir because HOME (APPDATA on Windows) isn't set, as
+/// in stripped-down containers and service shells.
+pub fn default_log_location() -> DefaultPath {
+    log_location_from(|name| std::env::var_os(name))
+}
+
+fn log_location_from(var: impl Fn(&str) -> Option<OsString>) -> DefaultPath {
+    let found = |path: PathBuf| DefaultPath {
+        path,
+        warning: None,
+    };
+    if let Some(path) = var("GOO_LOG_PATH") {
         let path = PathBuf::from(path);
         if !path.as_os_str().is_empty() {
-            return Some(path);
+            return found(path);
+        }
+    }
+
+    if let Some(paths) = var("GOO_LOG_SEARCH_PATHS") {
+        if let Some(path) = search_log_path(&paths) {
+            return found(path);
         }
     }
 
     #[cfg(target_os = "windows")]
     {
-        if let Some(appdata) = std::env::var_os("APPDATA") {
+        if let Some(appdata) = var("APPDATA").filter(|value| !value.is_empty()) {
             let base = PathBuf::from(appdata);
             let vlc = base.join("vlc");
             if vlc.exists() {
-                return Some(vlc.join(".goo_watch_log.txt"));
+                return found(vlc.join(".goo_watch_log.txt"));
             }
-            return Some(base.join(".goo_watch_log.txt"));
+            return found(base.join(".goo_watch_log.txt"));
         }
     }
 
     #[cfg(not(target_os = "windows"))]
     {
-        if let Some(home) = std::env::var_os("HOME") {
+        if let Some(home) = var("HOME").filter(|value| !value.is_empty()) {
             let home = PathBuf::from(home);
             let candidates = [
                 home.join(".local/share/vlc"),
@@ -77,14 +810,114 @@ pub fn default_log_path() -> Option<PathBuf> {
             ];
             for base in candidates {
                 if base.exists() {
-                    return Some(base.join(".goo_watch_log.txt"));
+                    return found(base.join(".goo_watch_log.txt"));
                 }
             }
-            return Some(home.join(".goo_watch_log.txt"));
+            return found(home.join(".goo_watch_log.txt"));
         }
     }
 
-    None
+    let path = std::env::temp_dir().join(".goo_watch_log.txt");
+    let warning = format!(
+        "{HOME_VAR} is not set, so the watch log defaults to {}, which may be cleared on reboot; \
+         set GOO_LOG_PATH to keep it somewhere permanent",
+        path.display()
+    );
+    DefaultPath {
+        path,
+        warning: Some(warning),
+    }
+}
+
+#[cfg(target_os = "windows")]
+const HOME_VAR: &str = "APPDATA";
+#[cfg(not(target_os = "windows"))]
+const HOME_VAR: &str = "HOME";
+
+/// The directory goo keeps its settings in, following the platform convention,
+/// or a `goo` folder in the temp dir (with a warning) when HOME/APPDATA is unset.
+pub fn config_location() -> DefaultPath {
+    config_location_from(|name| std::env::var_os(name))
+}
+
+fn config_location_from(var: impl Fn(&str) -> Option<OsString>) -> DefaultPath {
+    let var = |name: &str| var(name).filter(|value| !value.is_empty());
+    #[cfg(target_os = "windows")]
+    let base = var("APPDATA").map(|base| PathBuf::from(base).join("goo"));
+    #[cfg(target_os = "macos")]
+    let base = var("HOME")
+        .map(|base| PathBuf::from(base).join("Library").join("Application Support").join("goo"));
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let base = match var("XDG_CONFIG_HOME") {
+        Some(base) => Some(PathBuf::from(base).join("goo")),
+        None => var("HOME").map(|base| PathBuf::from(base).join(".config").join("goo")),
+    };
+
+    match base {
+        Some(path) => DefaultPath {
+            path,
+            warning: None,
+        },
+        None => {
+            let path = std::env::temp_dir().join("goo");
+            let warning = format!(
+                "{HOME_VAR} is not set, so settings are kept in {}, which may be cleared on reboot",
+                path.display()
+            );
+            DefaultPath {
+                path,
+                warning: Some(warning),
+            }
+        }
+    }
+}
+
+/// Picks the first existing directory from a platform path list (`:`/`;` separated).
+fn search_log_path(paths: &OsStr) -> Option<PathBuf> {
+    std::env::split_paths(paths)
+        .filter(|base| !base.as_os_str().is_empty())
+        .find(|base| base.exists())
+        .map(|base| base.join(".goo_watch_log.txt"))
+}
+
+/// The log a command works on: `arg` when one was passed, otherwise
+/// [`default_log_location`] and its warning. The CLI and the desktop app both
+/// resolve through here so every command agrees on where the log is.
+pub fn resolve_log_path(arg: Option<&str>) -> DefaultPath {
+    match arg {
+        Some(value) => DefaultPath {
+            path: PathBuf::from(value),
+            warning: None,
+        },
+        None => default_log_location(),
+    }
+}
+
+/// The cache for `log_path`: `arg` when one was passed, otherwise
+/// [`default_cache_path`].
+pub fn resolve_cache_path(arg: Option<&Path>, log_path: &Path) -> PathBuf {
+    arg.map(PathBuf::from).unwrap_or_else(|| default_cache_path(log_path))
+}
+
+/// Whether a cache could be saved at `path`, without creating anything. An
+/// existing cache is opened for appending and left as it was. Otherwise a
+/// probe file is written to, and removed from, the nearest folder that already
+/// exists on the way to `path`; [`MovieCache::save`] creates the rest.
+pub fn check_cache_writable(path: &Path) -> std::io::Result<()> {
+    if path.exists() {
+        return std::fs::OpenOptions::new().append(true).open(path).map(|_| ());
+    }
+    let mut folder = path.parent().filter(|parent| !parent.as_os_str().is_empty());
+    while let Some(missing) = folder.filter(|folder| !folder.exists()) {
+        folder = missing.parent().filter(|parent| !parent.as_os_str().is_empty());
+    }
+    let folder = folder.unwrap_or(Path::new("."));
+    if !folder.is_dir() {
+        return Err(std::io::Error::other(format!("{} is not a folder", folder.display())));
+    }
+    let probe = folder.join(format!(".goo_cache_probe_{}", std::process::id()));
+    std::fs::write(&probe, b"")?;
+    std::fs::remove_file(&probe)
 }
 
 pub fn default_cache_path(log_path: &Path) -> PathBuf {
@@ -94,10 +927,177 @@ pub fn default_cache_path(log_path: &Path) -> PathBuf {
         .unwrap_or_else(|| PathBuf::from(".goo_cache.json"))
 }
 
+/// Series names to always search as TV, one per line, kept next to the log.
+pub fn default_force_tv_path(log_path: &Path) -> PathBuf {
+    log_path
+        .parent()
+        .map(|parent| parent.join(".goo_force_tv.txt"))
+        .unwrap_or_else(|| PathBuf::from(".goo_force_tv.txt"))
+}
+
+/// Hand-made title -> TMDB id matches (see [`TitleOverrides`]), kept next to the log.
+pub fn default_overrides_path(log_path: &Path) -> PathBuf {
+    log_path
+        .parent()
+        .map(|parent| parent.join(".goo_overrides.json"))
+        .unwrap_or_else(|| PathBuf::from(".goo_overrides.json"))
+}
+
+/// Removes every watch of `cleaned_title` (case-insensitive) from `release_year`
+/// by rewriting the log. Lines are cleaned with `profile`, which should be the
+/// one the history was shown with. Only lines that parse as a matching entry go; comments,
+/// blank lines and malformed lines are written back byte for byte, line endings
+/// and a missing final newline included. Returns whether anything was removed;
+/// a missing log counts as nothing to remove.
+pub fn delete_log_entries(
+    path: &Path,
+    cleaned_title: &str,
+    release_year: Option<i32>,
+    profile: CleanProfile,
+) -> std::io::Result<bool> {
+    let target = cleaned_title.trim().to_lowercase();
+    if target.is_empty() {
+        return Ok(false);
+    }
+
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(err) => return Err(err),
+    };
+
+    let cleaner = profile.cleaner();
+    let mut kept = String::with_capacity(content.len());
+    let mut removed_any = false;
+    for line in content.split_inclusive('\n') {
+        let should_remove = parse_log_line_with(line, &cleaner).is_some_and(|entry| {
+            entry.cleaned_title.trim().to_lowercase() == target && entry.release_year == release_year
+        });
+        if should_remove {
+            removed_any = true;
+        } else {
+            kept.push_str(line);
+        }
+    }
+    if !removed_any {
+        return Ok(false);
+    }
+
+    std::fs::write(path, kept)?;
+    Ok(true)
+}
+
+/// Starts VLC on `source_path`, a logged path or MRL, without waiting for it to
+/// exit. `GOO_VLC_PATH` overrides where VLC is looked for.
+pub fn play_in_vlc(source_path: &str) -> Result<(), AppError> {
+    launch_vlc(&vlc_binary(|name| std::env::var_os(name)), source_path)
+}
+
+fn launch_vlc(vlc: &Path, source_path: &str) -> Result<(), AppError> {
+    match Command::new(vlc).args(vlc_args(source_path)).spawn() {
+        Ok(_) => Ok(()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            Err(AppError::VlcNotFound(vlc.to_path_buf()))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// VLC's arguments for `source_path`. `--` ends VLC's options, so a file
+/// named like `-Movie.mkv` is opened rather than read as a flag.
+fn vlc_args(source_path: &str) -> [String; 2] {
+    ["--".to_string(), vlc_target(source_path)]
+}
+
+/// What VLC is asked to open. Windows resolves a UNC path (`//nas/share/...`)
+/// itself; elsewhere it names no file, so VLC gets the share as an `smb://` MRL.
+fn vlc_target(source_path: &str) -> String {
+    if cfg!(windows) {
+        return source_path.to_string();
+    }
+    let unc = source_path
+        .strip_prefix("//")
+        .or_else(|| source_path.strip_prefix("\\\\"))
+        .filter(|rest| !rest.starts_with(['/', '\\']));
+    match unc {
+        Some(rest) => format!("smb://{}", mrl_encode(&rest.replace('\\', "/"))),
+        None => source_path.to_string(),
+    }
+}
+
+/// Percent-encodes everything but unreserved characters and `/`.
+fn mrl_encode(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+/// `GOO_VLC_PATH`, else the standard install location where the platform has
+/// one, else `vlc` from PATH.
+fn vlc_binary(var: impl Fn(&str) -> Option<OsString>) -> PathBuf {
+    if let Some(path) = var("GOO_VLC_PATH").filter(|value| !value.is_empty()) {
+        return PathBuf::from(path);
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let bundled = PathBuf::from("/Applications/VLC.app/Contents/MacOS/VLC");
+        if bundled.exists() {
+            return bundled;
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        for name in ["ProgramFiles", "ProgramFiles(x86)"] {
+            if let Some(base) = var(name).filter(|value| !value.is_empty()) {
+                let exe = PathBuf::from(base).join("VideoLAN").join("VLC").join("vlc.exe");
+                if exe.exists() {
+                    return exe;
+                }
+            }
+        }
+    }
+
+    PathBuf::from("vlc")
+}
+
 impl std::fmt::Display for AppError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            #[allow(deprecated)]
             AppError::MissingLogPath => write!(f, "log path is missing"),
+            AppError::UnknownMovie(id) => write!(f, "TMDB has no movie with id {id}"),
+            AppError::InvalidDate(value) => write!(
+                f,
+                "invalid date {value:?}; expected YYYY-MM-DD or an ISO 8601 timestamp"
+            ),
+            AppError::VlcNotFound(path) => write!(
+                f,
+                "VLC was not found at {}; install VLC or set GOO_VLC_PATH to its executable",
+                path.display()
+            ),
+            AppError::CacheUnwritable { path, reason } => write!(
+                f,
+                "cache {} is not writable ({reason}); pass a writable cache path, \
+                 or choose to enrich without saving",
+                path.display()
+            ),
+            AppError::CacheCorrupted(path) => write!(
+                f,
+                "cache {} is unreadable; run `goo cache repair` before changing it",
+                path.display()
+            ),
+            AppError::InvalidExport { path, reason } => {
+                write!(f, "{} is not a Trakt history export: {reason}", path.display())
+            }
             AppError::Io(error) => write!(f, "io error: {error}"),
             AppError::Tmdb(error) => write!(f, "{error}"),
         }
@@ -117,3 +1117,541 @@ impl From<TmdbError> for AppError {
         AppError::Tmdb(error)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_log_line;
+    use crate::test_support::{enriched, mock_server, scratch_dir};
+
+    #[test]
+    fn stats_describe_the_returned_entries() {
+        let root = scratch_dir("history-stats");
+        let log_path = root.join("log.txt");
+        std::fs::write(
+            &log_path,
+            "2025-01-01T20:00:00Z|/movies/Dune.2021.1080p.mkv\n\
+             2025-01-02T20:00:00Z|/movies/Alien.1979.mkv\n\
+             2025-01-03T20:00:00Z|/movies/Dune.2021.2160p.mkv\n",
+        )
+        .expect("write log");
+        let (base_url, _requests) = mock_server(vec![
+            (200, r#"{"results":[{"id":438631,"title":"Dune"}]}"#),
+            (200, r#"{"results":[{"id":348,"title":"Alien"}]}"#),
+        ]);
+        let client = TmdbClient::new("key").with_base_url(base_url);
+
+        let request = HistoryRequest::new(&log_path);
+        let (history, stats) = history_with_stats(request, &client).expect("history");
+
+        let mut expected = WatchStats::default();
+        for entry in &history.entries {
+            expected.record(&entry.cleaned_title, entry.release_year);
+        }
+        assert_eq!(stats, expected);
+        assert_eq!(stats.total_entries, 3);
+        assert_eq!(stats.unique_titles(), 2);
+        assert_eq!(stats.decades.get(&2020), Some(&2));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn abandoned_watches_are_left_out_when_asked() {
+        let root = scratch_dir("min_watched");
+        let log_path = root.join("watch.txt");
+        let log = [
+            r#"{"path":"/films/Dune.2021.mkv","position":0.02,"duration":9300}"#,
+            r#"{"path":"/films/Alien.1979.mkv","position":0.5,"duration":400}"#,
+            r#"{"path":"/films/Heat.1995.mkv","position":0.9,"duration":10200}"#,
+            r#"{"path":"/films/Arrival.2016.mkv","position":0.3}"#,
+            "Tenet.2020.mkv",
+        ];
+        std::fs::write(&log_path, log.join("\n")).expect("write log");
+        let (base_url, _requests) = mock_server(vec![
+            (200, r#"{"results":[{"id":949,"title":"Heat"}]}"#),
+            (200, r#"{"results":[{"id":329865,"title":"Arrival"}]}"#),
+            (200, r#"{"results":[{"id":577922,"title":"Tenet"}]}"#),
+        ]);
+        let client = TmdbClient::new("key").with_base_url(base_url);
+
+        let request = HistoryRequest {
+            min_watched: WatchThreshold {
+                min_fraction: 0.1,
+                min_seconds: 300,
+            },
+            ..HistoryRequest::new(&log_path)
+        };
+        let (history, _) = history_with_stats(request, &client).expect("history");
+        let titles: Vec<&str> =
+            history.entries.iter().map(|entry| entry.cleaned_title.as_str()).collect();
+        assert_eq!(titles, vec!["Heat", "Arrival", "Tenet"]);
+
+        let everything = WatchThreshold::default();
+        let dune = crate::parse_log_line(log[0]).expect("entry");
+        assert!(everything.keeps(&dune));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn split_releases_enrich_as_one_watch() {
+        let root = scratch_dir("merge_parts");
+        let log_path = root.join("watch.txt");
+        let log = [
+            "2025-01-01T20:00:00Z|Heat.1995.CD1.avi",
+            "2025-01-01T21:10:00Z|Heat.1995.CD2.avi",
+        ];
+        std::fs::write(&log_path, log.join("\n")).expect("write log");
+        let (base_url, _requests) =
+            mock_server(vec![(200, r#"{"results":[{"id":949,"title":"Heat"}]}"#)]);
+        let client = TmdbClient::new("key").with_base_url(base_url);
+
+        let (history, _) =
+            history_with_stats(HistoryRequest::new(&log_path), &client).expect("history");
+        assert_eq!(history.entries.len(), 1);
+        assert_eq!(history.entries[0].watched_at.as_deref(), Some("2025-01-01T20:00:00Z"));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn concurrent_lookups_keep_log_order_and_feed_stats() {
+        let root = scratch_dir("concurrent_history");
+        let log_path = root.join("watch.txt");
+        let log = ["Heat.1995.mkv", "Alien.1979.mkv", "Heat.1995.mkv"];
+        std::fs::write(&log_path, log.join("\n")).expect("write log");
+        // Workers may take either response, so both name the same film.
+        const HEAT: &str = r#"{"results":[{"id":949,"title":"Heat"}]}"#;
+        let (base_url, requests) = mock_server(vec![(200, HEAT), (200, HEAT)]);
+        let client = TmdbClient::new("key").with_base_url(base_url);
+
+        let request = HistoryRequest {
+            concurrency: 2,
+            ..HistoryRequest::new(&log_path)
+        };
+        let (history, stats) = history_with_stats(request, &client).expect("history");
+        let titles: Vec<&str> =
+            history.entries.iter().map(|entry| entry.cleaned_title.as_str()).collect();
+        assert_eq!(titles, vec!["Heat", "Alien", "Heat"]);
+        assert!(history.entries.iter().all(|entry| entry.movie.is_some()));
+        assert_eq!(stats.total_entries, 3);
+        assert!(requests.recv().is_ok() && requests.recv().is_ok());
+        assert!(requests.try_recv().is_err(), "repeated titles are looked up once");
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn date_ranges_filter_before_enrichment() {
+        let root = scratch_dir("date_range");
+        let log_path = root.join("watch.txt");
+        let log = [
+            "2023-12-31T23:59:59Z|Alien.1979.mkv",
+            "2024-01-01T00:00:00Z|Dune.2021.mkv",
+            "2024-12-31T22:00:00+00:00|Heat.1995.mkv",
+            "Undated.2001.mkv",
+            "2025-01-01T00:00:00Z|Tenet.2020.mkv",
+        ];
+        std::fs::write(&log_path, log.join("\n")).expect("write log");
+        let (base_url, requests) = mock_server(vec![
+            (200, r#"{"results":[{"id":438631,"title":"Dune"}]}"#),
+            (200, r#"{"results":[{"id":949,"title":"Heat"}]}"#),
+        ]);
+        let client = TmdbClient::new("key").with_base_url(base_url);
+
+        let range = WatchRange::parse(Some("2024-01-01"), Some("2024-12-31")).expect("range");
+        let history = range_history(&log_path, None, &client, range).expect("history");
+        let titles: Vec<&str> =
+            history.entries.iter().map(|entry| entry.cleaned_title.as_str()).collect();
+        assert_eq!(titles, vec!["Dune", "Heat"]);
+        assert!(requests.recv().expect("dune").contains("query=Dune"));
+        assert!(requests.recv().expect("heat").contains("query=Heat"));
+        assert!(requests.try_recv().is_err());
+
+        let since = WatchRange::parse(Some("2024-12-31T23:00:00Z"), None).expect("since");
+        let undated = parse_log_line("Undated.2001.mkv").expect("entry");
+        assert!(!since.contains(&undated));
+        assert!(WatchRange::default().contains(&undated));
+
+        let err = WatchRange::parse(None, Some("last tuesday")).expect_err("bad bound");
+        assert!(matches!(err, AppError::InvalidDate(value) if value == "last tuesday"));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn doctor_flags_what_blocks_enrichment() {
+        let root = scratch_dir("doctor");
+        let log_path = root.join("log.txt");
+        let missing = diagnose_with(&log_path, None, Err(TmdbError::MissingApiKey));
+        let failed: Vec<&str> = missing
+            .iter()
+            .filter(|check| !check.passed && check.critical)
+            .map(|check| check.name)
+            .collect();
+        assert_eq!(failed, vec!["Log file", "TMDB credentials"]);
+        assert!(missing.iter().all(|check| check.name != "TMDB search"));
+
+        std::fs::write(&log_path, "2025-01-01T20:00:00Z|/movies/Dune.2021.mkv\n").expect("log");
+        std::fs::write(root.join("cache.json"), "{not json").expect("cache");
+        let (base_url, requests) =
+            mock_server(vec![(200, r#"{"results":[{"id":438631,"title":"Dune"}]}"#)]);
+        let client = TmdbClient::new("key").with_base_url(base_url);
+        let checks = diagnose_with(&log_path, Some(&root.join("cache.json")), Ok(client));
+
+        let summary: Vec<(&str, bool, bool)> =
+            checks.iter().map(|check| (check.name, check.passed, check.critical)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Log file", true, false),
+                ("Log entries", true, false),
+                ("Cache", false, false),
+                ("TMDB credentials", true, false),
+                ("TMDB search", true, false),
+            ]
+        );
+        assert_eq!(checks[1].detail, "1 parsed");
+        assert!(requests.recv().expect("search").contains("query=Dune"));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn sorts_entries_by_title_or_date() {
+        let log = [
+            "2025-01-03T20:00:00Z|heat.1995.mkv",
+            "Undated.2001.mkv",
+            "2025-01-01T20:00:00-05:00|Alien.1979.mkv",
+            "2025-01-02T00:00:00Z|Dune.2021.mkv",
+        ];
+        let entries: Vec<WatchEntry> = log.into_iter().filter_map(parse_log_line).collect();
+        let sorted = |order, reverse| {
+            let mut entries = entries.clone();
+            sort_entries(&mut entries, order, reverse);
+            entries.into_iter().map(|entry| entry.cleaned_title).collect::<Vec<_>>()
+        };
+
+        assert_eq!(sorted(EntryOrder::File, false), vec!["heat", "Undated", "Alien", "Dune"]);
+        assert_eq!(sorted(EntryOrder::Title, false), vec!["Alien", "Dune", "heat", "Undated"]);
+        assert_eq!(sorted(EntryOrder::Title, true), vec!["Undated", "heat", "Dune", "Alien"]);
+        assert_eq!(sorted(EntryOrder::Date, false), vec!["Dune", "Alien", "heat", "Undated"]);
+        assert_eq!(sorted(EntryOrder::Date, true), vec!["heat", "Alien", "Dune", "Undated"]);
+        assert_eq!(EntryOrder::from_name("Date"), Some(EntryOrder::Date));
+        assert_eq!(EntryOrder::from_name("size"), None);
+    }
+
+    #[test]
+    fn unset_home_falls_back_to_temp_dir_with_warning() {
+        let unset = |_: &str| None;
+
+        let log = log_location_from(unset);
+        assert_eq!(log.path, std::env::temp_dir().join(".goo_watch_log.txt"));
+        assert!(log.warning.as_deref().is_some_and(|warning| warning.contains(HOME_VAR)));
+
+        let config = config_location_from(unset);
+        assert_eq!(config.path, std::env::temp_dir().join("goo"));
+        assert!(config.warning.is_some());
+    }
+
+    #[test]
+    fn history_reports_which_files_it_read() {
+        let root = scratch_dir("history_sources");
+        let log = root.join(".goo_watch_log.txt");
+
+        let first = load_enriched_history(&log, None, Some("key")).expect("history");
+        assert_eq!(first.log_path, log);
+        assert!(!first.log_existed);
+        assert_eq!(first.cache_path, root.join(".goo_cache.json"));
+        assert!(!first.cache_existed);
+
+        std::fs::write(&log, "# nothing watched yet\n").expect("write log");
+        let second = load_enriched_history(&log, None, Some("key")).expect("history");
+        assert!(second.log_existed);
+        assert!(second.cache_existed);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn malformed_overrides_are_a_warning() {
+        let root = scratch_dir("malformed_overrides");
+        let log = root.join(".goo_watch_log.txt");
+        std::fs::write(&log, "# nothing watched yet\n").expect("write log");
+        std::fs::write(default_overrides_path(&log), "{\"Amelie\": 194,}").expect("write");
+
+        let history = load_enriched_history(&log, None, Some("key")).expect("history");
+        let warning = history.overrides_warning.expect("warning");
+        assert!(warning.contains(".goo_overrides.json"), "{warning}");
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn corrupt_caches_are_left_for_repair() {
+        let root = scratch_dir("corrupt_cache");
+        let log = root.join(".goo_watch_log.txt");
+        let cache_path = root.join(".goo_cache.json");
+        std::fs::write(&log, "# nothing watched yet\n").expect("write log");
+        std::fs::write(&cache_path, "{\"entries\":{\"dune|2021\":").expect("write cache");
+
+        let history = load_enriched_history(&log, None, Some("key")).expect("history");
+        assert_eq!(history.cache_status, CacheStatus::LoadCorrupted { path: cache_path.clone() });
+        assert_eq!(history.cache_warning, history.cache_status.warning());
+        let err = clear_cache_misses(&log, None).expect_err("corrupt");
+        assert!(matches!(&err, AppError::CacheCorrupted(path) if *path == cache_path));
+        assert_eq!(
+            std::fs::read_to_string(&cache_path).expect("read cache"),
+            "{\"entries\":{\"dune|2021\":"
+        );
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn unwritable_caches_are_caught_before_any_lookups() {
+        let root = scratch_dir("unwritable_cache");
+        let log_path = root.join("watch.txt");
+        std::fs::write(&log_path, "2025-01-01T20:00:00Z|/movies/Dune.2021.mkv\n").expect("log");
+        // A folder path that runs through a file can never be created.
+        let blocker = root.join("blocker");
+        std::fs::write(&blocker, "").expect("blocker");
+        let cache_path = blocker.join("cache.json");
+        assert!(check_cache_writable(&cache_path).is_err());
+        assert!(check_cache_writable(&root.join("cache.json")).is_ok());
+        assert!(!root.join("cache.json").exists());
+        assert!(check_cache_writable(&root.join("new/cache.json")).is_ok());
+        assert!(!root.join("new").exists(), "checking created the folder");
+
+        let (base_url, requests) =
+            mock_server(vec![(200, r#"{"results":[{"id":438631,"title":"Dune"}]}"#)]);
+        let client = TmdbClient::new("key").with_base_url(base_url);
+        let request = |on_unwritable_cache| HistoryRequest {
+            cache_path: Some(&cache_path),
+            on_unwritable_cache,
+            ..HistoryRequest::new(&log_path)
+        };
+
+        let err = history_with_stats(request(UnwritableCache::Fail), &client).expect_err("fail");
+        assert!(matches!(&err, AppError::CacheUnwritable { path, .. } if *path == cache_path));
+        assert!(requests.try_recv().is_err(), "no lookups before failing");
+
+        let (history, _) =
+            history_with_stats(request(UnwritableCache::InMemory), &client).expect("in memory");
+        assert_eq!(history.entries[0].movie.as_ref().map(|movie| movie.id), Some(438631));
+        assert!(matches!(history.cache_status, CacheStatus::Unwritable { .. }));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn trakt_imports_append_to_the_log() {
+        let root = scratch_dir("trakt_import");
+        let export_path = root.join("history.json");
+        let log_path = root.join("watch.txt");
+        std::fs::write(
+            &export_path,
+            r#"[{"watched_at": "2024-03-01T20:00:00.000Z", "type": "movie",
+                 "movie": {"title": "Dune", "year": 2021, "ids": {"tmdb": 438631}}}]"#,
+        )
+        .expect("export");
+        std::fs::write(&log_path, "2025-01-01T20:00:00Z|/movies/Heat.1995.mkv").expect("log");
+
+        let import = import_trakt(&export_path, &log_path).expect("import");
+        assert_eq!(import.lines.len(), 1);
+        let entries = crate::read_watch_log(&log_path).expect("read");
+        let titles: Vec<_> = entries.iter().map(|entry| entry.cleaned_title.as_str()).collect();
+        assert_eq!(titles, ["Heat", "Dune"]);
+
+        std::fs::write(&export_path, r#"{"movies": []}"#).expect("export");
+        let err = import_trakt(&export_path, &log_path).expect_err("not an export");
+        assert!(matches!(err, AppError::InvalidExport { .. }));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn passed_paths_win_over_defaults() {
+        let log = resolve_log_path(Some("/logs/watch.txt"));
+        assert_eq!(log.path, PathBuf::from("/logs/watch.txt"));
+        assert_eq!(log.warning, None);
+
+        let cache = resolve_cache_path(Some(Path::new("/caches/goo.json")), &log.path);
+        assert_eq!(cache, PathBuf::from("/caches/goo.json"));
+        let cache = resolve_cache_path(None, &log.path);
+        assert_eq!(cache, PathBuf::from("/logs/.goo_cache.json"));
+    }
+
+    #[test]
+    fn missing_vlc_is_reported_with_where_it_was_looked_for() {
+        let custom = |name: &str| (name == "GOO_VLC_PATH").then(|| OsString::from("/opt/vlc"));
+        assert_eq!(vlc_binary(custom), PathBuf::from("/opt/vlc"));
+
+        let root = scratch_dir("missing_vlc");
+        let vlc = root.join("no-such-vlc");
+        let err = launch_vlc(&vlc, "/movies/Dune.2021.mkv").expect_err("no vlc");
+        assert!(matches!(&err, AppError::VlcNotFound(path) if *path == vlc));
+        assert!(err.to_string().contains("GOO_VLC_PATH"));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn vlc_gets_paths_after_the_end_of_options() {
+        assert_eq!(vlc_args("/movies/-Dune.mkv"), ["--", "/movies/-Dune.mkv"]);
+        assert_eq!(vlc_target("http://host/stream.m3u8"), "http://host/stream.m3u8");
+        let unc = vlc_target("//nas/share/Heat [1995].mkv");
+        if cfg!(windows) {
+            assert_eq!(unc, "//nas/share/Heat [1995].mkv");
+        } else {
+            assert_eq!(unc, "smb://nas/share/Heat%20%5B1995%5D.mkv");
+            assert_eq!(vlc_target("\\\\nas\\share\\Dune.mkv"), "smb://nas/share/Dune.mkv");
+        }
+    }
+
+    #[test]
+    fn empty_home_counts_as_unset_but_goo_log_path_still_wins() {
+        let empty_home = |name: &str| (name == HOME_VAR).then(OsString::new);
+        assert!(log_location_from(empty_home).warning.is_some());
+
+        let explicit =
+            |name: &str| (name == "GOO_LOG_PATH").then(|| OsString::from("/logs/watch.txt"));
+        let log = log_location_from(explicit);
+        assert_eq!(log.path, PathBuf::from("/logs/watch.txt"));
+        assert_eq!(log.warning, None);
+    }
+
+    #[test]
+    fn comments_are_skipped_on_read_and_kept_on_delete() {
+        let root = scratch_dir("log-comments");
+        let log_path = root.join("log.txt");
+        std::fs::write(
+            &log_path,
+            "# rewatched with the kids\n\
+             2025-01-01T20:00:00Z|/movies/Dune.2021.1080p.mkv\n\
+             #2025-01-02T20:00:00Z|/movies/Dune.2021.mkv\n\
+             2025-01-03T20:00:00Z|/movies/Alien.1979.mkv\n",
+        )
+        .expect("write log");
+
+        let titles: Vec<String> = crate::read_watch_log(&log_path)
+            .expect("read")
+            .into_iter()
+            .map(|entry| entry.cleaned_title)
+            .collect();
+        assert_eq!(titles, vec!["Dune", "Alien"]);
+
+        let deleted = delete_log_entries(&log_path, "dune", Some(2021), CleanProfile::Standard);
+        assert!(deleted.expect("delete"));
+        assert_eq!(
+            std::fs::read_to_string(&log_path).expect("read back"),
+            "# rewatched with the kids\n\
+             #2025-01-02T20:00:00Z|/movies/Dune.2021.mkv\n\
+             2025-01-03T20:00:00Z|/movies/Alien.1979.mkv\n"
+        );
+        let again = delete_log_entries(&log_path, "Dune", Some(2021), CleanProfile::Standard);
+        assert!(!again.expect("delete again"));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn deletes_match_titles_cleaned_with_the_given_profile() {
+        let root = scratch_dir("log-delete-profile");
+        let log_path = root.join("log.txt");
+        let log = "2025-01-01T20:00:00Z|/movies/Spider-Man.2002.mkv\n";
+        std::fs::write(&log_path, log).expect("write log");
+
+        // Plex keeps the hyphen the standard profile splits on.
+        let standard =
+            delete_log_entries(&log_path, "Spider-Man", Some(2002), CleanProfile::Standard);
+        assert!(!standard.expect("delete"));
+        let plex = delete_log_entries(&log_path, "Spider-Man", Some(2002), CleanProfile::Plex);
+        assert!(plex.expect("delete"));
+        assert_eq!(std::fs::read_to_string(&log_path).expect("read back"), "");
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn delete_keeps_malformed_and_blank_lines_in_place() {
+        let root = scratch_dir("log-malformed");
+        let log_path = root.join("log.txt");
+        std::fs::write(
+            &log_path,
+            "2025-01-01T20:00:00Z|/movies/Dune.2021.mkv\r\n\
+             |\r\n\
+             \r\n\
+             2025-01-02T20:00:00Z|\n\
+             # note\n\
+             2025-01-03T20:00:00Z|/movies/Dune.2021.1080p.mkv\n\
+             2025-01-04T20:00:00Z|/movies/Dune.1984.mkv\n\
+             not even a timestamp\n\
+             2025-01-05T20:00:00Z|/movies/Dune.2021.mkv",
+        )
+        .expect("write log");
+
+        let deleted = delete_log_entries(&log_path, "Dune", Some(2021), CleanProfile::Standard);
+        assert!(deleted.expect("delete"));
+        assert_eq!(
+            std::fs::read_to_string(&log_path).expect("read back"),
+            "|\r\n\
+             \r\n\
+             2025-01-02T20:00:00Z|\n\
+             # note\n\
+             2025-01-04T20:00:00Z|/movies/Dune.1984.mkv\n\
+             not even a timestamp\n"
+        );
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn groups_entries_by_utc_month() {
+        let entries: Vec<WatchEntry> = [
+            "2025-01-31T23:30:00-01:00|/movies/Heat.1995.mkv",
+            "2025-01-31T22:00:00Z|/movies/Alien.1979.mkv",
+            "/movies/Undated.mkv",
+            "2024-12-31T23:59:59Z|/movies/Dune.2021.mkv",
+            "garbage|/movies/Tenet.2020.mkv",
+            "2025-02-01T00:00:00Z|/movies/Ran.1985.mkv",
+        ]
+        .into_iter()
+        .filter_map(parse_log_line)
+        .collect();
+
+        let months = group_by_month(entries);
+        let titles = |month: &str| -> Vec<&str> {
+            months[month].iter().map(|entry| entry.cleaned_title.as_str()).collect()
+        };
+        let keys: Vec<&str> = months.keys().map(String::as_str).collect();
+        assert_eq!(keys, vec!["2024-12", "2025-01", "2025-02", UNKNOWN_MONTH]);
+        assert_eq!(titles("2024-12"), vec!["Dune"]);
+        assert_eq!(titles("2025-01"), vec!["Alien"]);
+        assert_eq!(titles("2025-02"), vec!["Heat", "Ran"]);
+        assert_eq!(titles(UNKNOWN_MONTH).len(), 2);
+    }
+
+    #[test]
+    fn pages_run_newest_first() {
+        let mut entries = vec![
+            enriched("Alien", Some("2025-01-01T20:00:00Z")),
+            enriched("Undated", None),
+            enriched("Dune", Some("2025-01-03T20:00:00+02:00")),
+            enriched("Heat", Some("2025-01-02T20:00:00Z")),
+        ];
+
+        sort_newest_first(&mut entries);
+        let titles = |page: &[EnrichedEntry]| -> Vec<String> {
+            page.iter().map(|entry| entry.cleaned_title.clone()).collect()
+        };
+        assert_eq!(titles(&entries), vec!["Dune", "Heat", "Alien", "Undated"]);
+        assert_eq!(titles(history_page(&entries, 1, Some(2))), vec!["Heat", "Alien"]);
+        assert_eq!(titles(history_page(&entries, 3, Some(10))), vec!["Undated"]);
+        assert_eq!(history_page(&entries, 2, None).len(), 2);
+        assert!(history_page(&entries, 9, Some(2)).is_empty());
+    }
+
+    #[test]
+    fn custom_search_paths_pick_first_existing() {
+        let root = scratch_dir("search-paths");
+        let missing = root.join("missing");
+        let first = root.join("first");
+        let second = root.join("second");
+        std::fs::create_dir_all(&first).expect("first");
+        std::fs::create_dir_all(&second).expect("second");
+
+        let paths = std::env::join_paths([&missing, &first, &second]).expect("join paths");
+        assert_eq!(search_log_path(&paths), Some(first.join(".goo_watch_log.txt")));
+
+        let paths = std::env::join_paths([&missing]).expect("join paths");
+        assert_eq!(search_log_path(&paths), None);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
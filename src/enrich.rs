@@ -1,86 +1,2805 @@
-use crate::tmdb::{TmdbClient, TmdbError, TmdbMovie, DEFAULT_POSTER_SIZE};
-use crate::WatchEntry;
+use crate::tmdb::{
+    imdb_url, MovieSource, TmdbClient, TmdbError, TmdbMovie, TmdbMovieDetails, TmdbTvShow,
+    DEFAULT_POSTER_SIZE, POSTER_SIZES,
+};
+use crate::{ascii_fold, parse_id_hint, parse_timestamp, Episode, IdHint, WatchEntry};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+/// Worker threads [`enrich_entries_parallel`] callers should start from.
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Cached matches scoring below this are retried by [`refine_entries`].
+pub const DEFAULT_REFINE_THRESHOLD: f32 = 0.8;
+
+/// Layout of the cache file written by this build. Version 4 keyed titles
+/// with trailing articles and punctuation as written, version 3 without
+/// folding accents or spacing, version 2 cached details without
+/// credits, version 1 was the unversioned `{"entries": ...}` layout and
+/// version 0 a bare map of matches.
+pub const CACHE_VERSION: u32 = 5;
+
+/// How long a cached match is trusted before it is looked up again.
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+/// Misses expire sooner, so films TMDB adds later are picked up.
+pub const DEFAULT_MISS_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Field names are part of the JSON and `--format ndjson` output; keep them stable.
 #[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub struct EnrichedEntry {
     pub watched_at: Option<String>,
     pub raw_title: String,
+    pub source_path: Option<String>,
     pub cleaned_title: String,
     pub release_year: Option<i32>,
+    pub is_stream: bool,
+    pub episode: Option<Episode>,
     pub movie: Option<TmdbMovie>,
+    /// The series, for entries that parsed as episodes.
+    pub show: Option<TmdbTvShow>,
+    /// How well the matched film's title fits the cleaned one, from 0 to 1;
+    /// 1 for films fetched by id. `None` without a film match.
+    pub confidence: Option<f32>,
+    /// The match scored below [`EnrichOptions::confidence_threshold`]. Such
+    /// entries are still returned so a UI can flag them.
+    pub low_confidence: bool,
+    /// Why the lookup failed; only [`enrich_entries_lenient`] keeps such
+    /// entries, unmatched. Left out of the JSON when there was no failure, so
+    /// output from before the field existed is unchanged.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
     pub tmdb_url: Option<String>,
+    /// Only filled by [`attach_imdb_ids`], for matched films.
+    pub imdb_url: Option<String>,
     pub poster_url: Option<String>,
+    /// Only filled by [`attach_details`]; plain enrichment leaves it empty.
+    pub details: Option<TmdbMovieDetails>,
+    /// Only filled by [`attach_keywords`].
+    pub keywords: Vec<String>,
+    /// Only filled by [`attach_directors`], which costs a credits request per film.
+    pub directors: Vec<String>,
 }
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct MovieCache {
-    entries: HashMap<String, Option<TmdbMovie>>,
+    #[serde(default)]
+    version: u32,
+    entries: HashMap<String, Cached<Option<TmdbMovie>>>,
+    /// Series lookups, keyed like `entries` but kept apart so a show and a
+    /// film with the same name don't collide.
+    #[serde(default)]
+    shows: HashMap<String, Cached<Option<TmdbTvShow>>>,
+    #[serde(default)]
+    details: HashMap<u32, TmdbMovieDetails>,
+    #[serde(default)]
+    keywords: HashMap<u32, Vec<String>>,
+    #[serde(default)]
+    directors: HashMap<u32, Vec<String>>,
+    /// IMDb id per TMDB movie id; `None` when TMDB has none, so it isn't asked again.
+    #[serde(default)]
+    imdb_ids: HashMap<u32, Option<String>>,
+    /// Overviews found through the language fallback chain; empty when none was.
+    #[serde(default)]
+    overviews: HashMap<u32, String>,
+    /// Match score per cache key, recorded when the entry was fetched.
+    #[serde(default)]
+    confidence: HashMap<String, f32>,
+    /// Keys whose match was chosen by hand; see [`pin_match`](MovieCache::pin_match).
+    #[serde(default)]
+    pinned: HashSet<String>,
+    /// Lookups in a row that found nothing, per key. Each one doubles how long
+    /// the miss is trusted, up to the match TTL.
+    #[serde(default)]
+    misses: HashMap<String, u32>,
+    #[serde(skip, default = "default_cache_ttl")]
+    ttl: Duration,
+    #[serde(skip, default = "default_miss_ttl")]
+    miss_ttl: Duration,
+    /// When the loaded file was last written (unix seconds), the best guess
+    /// at the age of lookups saved without a timestamp.
+    #[serde(skip)]
+    file_mtime: Option<u64>,
+}
+
+/// A lookup result and when it was fetched (unix seconds). Caches written
+/// before timestamps were kept hold the bare value, which reads back with no
+/// timestamp and so counts as expired; such entries are written back bare.
+#[derive(Debug, Clone)]
+struct Cached<T> {
+    value: T,
+    fetched_at: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum StoredLookup<T> {
+    Stamped { value: T, fetched_at: u64 },
+    Bare(T),
+}
+
+impl<T: Serialize> Serialize for Cached<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.fetched_at {
+            Some(fetched_at) => StoredLookup::Stamped {
+                value: &self.value,
+                fetched_at,
+            },
+            None => StoredLookup::Bare(&self.value),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Cached<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match StoredLookup::deserialize(deserializer)? {
+            StoredLookup::Stamped { value, fetched_at } => Cached {
+                value,
+                fetched_at: Some(fetched_at),
+            },
+            StoredLookup::Bare(value) => Cached {
+                value,
+                fetched_at: None,
+            },
+        })
+    }
+}
+
+impl<T> Cached<T> {
+    fn now(value: T) -> Self {
+        Self {
+            value,
+            fetched_at: Some(unix_now()),
+        }
+    }
+
+    fn is_fresh(&self, ttl: Duration) -> bool {
+        self.fetched_at
+            .is_some_and(|fetched_at| unix_now().saturating_sub(fetched_at) < ttl.as_secs())
+    }
+}
+
+impl<T> Cached<Option<T>> {
+    fn is_fresh_within(&self, ttl: Duration, miss_ttl: Duration) -> bool {
+        self.is_fresh(if self.value.is_some() { ttl } else { miss_ttl })
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn default_cache_ttl() -> Duration {
+    DEFAULT_CACHE_TTL
+}
+
+fn default_miss_ttl() -> Duration {
+    DEFAULT_MISS_TTL
+}
+
+impl Default for MovieCache {
+    fn default() -> Self {
+        Self {
+            version: CACHE_VERSION,
+            entries: HashMap::new(),
+            shows: HashMap::new(),
+            details: HashMap::new(),
+            keywords: HashMap::new(),
+            directors: HashMap::new(),
+            imdb_ids: HashMap::new(),
+            overviews: HashMap::new(),
+            confidence: HashMap::new(),
+            pinned: HashSet::new(),
+            misses: HashMap::new(),
+            ttl: DEFAULT_CACHE_TTL,
+            miss_ttl: DEFAULT_MISS_TTL,
+            file_mtime: None,
+        }
+    }
 }
 
 impl MovieCache {
+    /// Re-look up matches older than `ttl`. Misses use the shorter of `ttl`
+    /// and the miss TTL.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self.miss_ttl = self.miss_ttl.min(ttl);
+        self
+    }
+
+    /// Re-look up titles that found nothing once they are older than `ttl`.
+    pub fn with_miss_ttl(mut self, ttl: Duration) -> Self {
+        self.miss_ttl = ttl;
+        self
+    }
+
+    /// Records `movie` as the match for `title`/`year`, replacing whatever the
+    /// search picked. Pinned matches never expire and are never refined, so
+    /// the correction outlives the TTL.
+    pub fn pin_match(&mut self, title: &str, year: Option<i32>, movie: TmdbMovie) {
+        let key = cache_key(title, year);
+        record_match(self, key.clone(), Some((movie, 1.0)));
+        self.pinned.insert(key);
+    }
+
+    /// Forgets every lookup that found nothing, films and series alike, so the
+    /// next enrichment searches for them again. Returns how many were dropped.
+    pub fn clear_misses(&mut self) -> usize {
+        let before = self.entries.len() + self.shows.len();
+        self.entries.retain(|_, cached| cached.value.is_some());
+        self.shows.retain(|_, cached| cached.value.is_some());
+        self.misses.clear();
+        before - self.entries.len() - self.shows.len()
+    }
+
+    /// How long a miss for `key` is trusted: the miss TTL, doubled for every
+    /// earlier miss in a row, but never longer than a match.
+    fn miss_ttl_for(&self, key: &str) -> Duration {
+        let repeats = self.misses.get(key).map_or(0, |count| count.saturating_sub(1));
+        let factor = 1u32.checked_shl(repeats.min(16)).unwrap_or(u32::MAX);
+        self.miss_ttl.saturating_mul(factor).min(self.ttl.max(self.miss_ttl))
+    }
+
+    fn fresh_movie(&self, key: &str) -> Option<&Option<TmdbMovie>> {
+        self.entries
+            .get(key)
+            .filter(|cached| {
+                self.pinned.contains(key)
+                    || cached.is_fresh_within(self.ttl, self.miss_ttl_for(key))
+            })
+            .map(|cached| &cached.value)
+    }
+
+    /// [`fresh_movie`](Self::fresh_movie), except that a match cached without
+    /// its raw JSON is looked up again when `client` asks for raw results.
+    fn usable_movie(&self, key: &str, client: &impl MovieSource) -> Option<&Option<TmdbMovie>> {
+        self.fresh_movie(key).filter(|cached| {
+            !client.includes_raw()
+                || self.pinned.contains(key)
+                || cached.as_ref().is_none_or(|movie| movie.raw.is_some())
+        })
+    }
+
+    fn fresh_show(&self, key: &str) -> Option<&Option<TmdbTvShow>> {
+        self.shows
+            .get(key)
+            .filter(|cached| cached.is_fresh_within(self.ttl, self.miss_ttl))
+            .map(|cached| &cached.value)
+    }
+
     pub fn load(path: &Path) -> Self {
+        Self::load_checked(path).0
+    }
+
+    /// Loads the cache, migrating older layouts to [`CACHE_VERSION`]. The
+    /// message explains anything that couldn't be loaded as-is, e.g. a cache
+    /// written by a newer goo, which is read as far as possible and then
+    /// protected from being overwritten by [`save`](Self::save).
+    pub fn load_checked(path: &Path) -> (Self, Option<String>) {
+        let (cache, status) = Self::load_with_status(path);
+        (cache, status.warning())
+    }
+
+    /// [`load_checked`](Self::load_checked) reporting what went wrong as a
+    /// [`CacheStatus`] instead of a message.
+    pub fn load_with_status(path: &Path) -> (Self, CacheStatus) {
+        let (mut cache, status) = Self::read_with_status(path);
+        cache.file_mtime = modified_unix(path);
+        (cache, status)
+    }
+
+    fn read_with_status(path: &Path) -> (Self, CacheStatus) {
         let Ok(content) = std::fs::read_to_string(path) else {
-            return Self::default();
+            return (Self::default(), CacheStatus::Ok);
+        };
+        let Ok(serde_json::Value::Object(root)) = serde_json::from_str(&content) else {
+            let status = CacheStatus::LoadCorrupted {
+                path: path.to_path_buf(),
+            };
+            return (Self::default(), status);
+        };
+
+        let version = stored_version(&root);
+        let mut repair = CacheRepair::default();
+        let mut cache = if version == 0 {
+            Self::salvage(root, version, &mut repair)
+        } else {
+            match serde_json::from_value::<Self>(serde_json::Value::Object(root.clone())) {
+                Ok(cache) => cache,
+                Err(_) => Self::salvage(root, version, &mut repair),
+            }
+        };
+        cache.migrate(version);
+        if version > CACHE_VERSION {
+            let status = CacheStatus::NewerVersion {
+                path: path.to_path_buf(),
+                version,
+            };
+            return (cache, status);
+        }
+        (cache, CacheStatus::Ok)
+    }
+
+    /// Keeps whatever parses from each map of a stored cache, entry by entry.
+    /// A format 0 cache is a bare map of title lookups.
+    fn salvage(
+        root: serde_json::Map<String, serde_json::Value>,
+        version: u32,
+        repair: &mut CacheRepair,
+    ) -> Self {
+        if version == 0 {
+            return Self {
+                entries: salvage_map(Some(&serde_json::Value::Object(root)), repair),
+                ..Self::default()
+            };
+        }
+        Self {
+            entries: salvage_map(root.get("entries"), repair),
+            shows: salvage_map(root.get("shows"), repair),
+            details: salvage_map(root.get("details"), repair),
+            keywords: salvage_map(root.get("keywords"), repair),
+            directors: salvage_map(root.get("directors"), repair),
+            imdb_ids: salvage_map(root.get("imdb_ids"), repair),
+            overviews: salvage_map(root.get("overviews"), repair),
+            confidence: salvage_map(root.get("confidence"), repair),
+            pinned: salvage_set(root.get("pinned"), repair),
+            misses: salvage_map(root.get("misses"), repair),
+            ..Self::default()
+        }
+    }
+
+    /// Brings a cache stored as format `version` up to [`CACHE_VERSION`]. A
+    /// newer format keeps its number so [`save`](Self::save) won't overwrite it.
+    fn migrate(&mut self, version: u32) {
+        if version < 3 {
+            // Fetched without credits; drop them so they're fetched again with cast and director.
+            self.details.clear();
+        }
+        if version < 5 {
+            self.rekey_titles();
+        }
+        self.version = version.max(CACHE_VERSION);
+    }
+
+    /// Recomputes title keys written before [`cache_key`] folded accents and
+    /// spacing, so "Amélie" and "Amelie" share one entry instead of two. Where
+    /// both spellings were cached, a pinned one wins, then the one already in
+    /// the new form, so a hand-picked match is never traded for a search result.
+    fn rekey_titles(&mut self) {
+        fn rekey<V>(map: &mut HashMap<String, V>, pinned: &HashSet<String>) {
+            let (current, stale): (Vec<_>, Vec<_>) = std::mem::take(map)
+                .into_iter()
+                .partition(|(key, _)| recompute_key(key) == *key);
+            map.extend(current);
+            for (key, value) in stale {
+                let new_key = recompute_key(&key);
+                if pinned.contains(&key) && !pinned.contains(&new_key) {
+                    map.insert(new_key, value);
+                } else {
+                    map.entry(new_key).or_insert(value);
+                }
+            }
+        }
+        rekey(&mut self.entries, &self.pinned);
+        rekey(&mut self.shows, &self.pinned);
+        rekey(&mut self.confidence, &self.pinned);
+        rekey(&mut self.misses, &self.pinned);
+        self.pinned = self.pinned.iter().map(|key| recompute_key(key)).collect();
+    }
+
+    /// Salvages what it can from a damaged cache file: a truncated tail is cut
+    /// back to the last complete value, and entries that no longer parse are
+    /// dropped one by one instead of discarding the whole cache. What survives
+    /// is migrated from older formats just as [`load`](Self::load) would.
+    pub fn load_lenient(path: &Path) -> std::io::Result<(Self, CacheRepair)> {
+        let content = std::fs::read_to_string(path)?;
+        let mut repair = CacheRepair::default();
+        let Some(serde_json::Value::Object(root)) = salvage_json(&content) else {
+            return Ok((Self::default(), repair));
         };
-        serde_json::from_str(&content).unwrap_or_default()
+
+        let version = stored_version(&root);
+        let mut cache = Self::salvage(root, version, &mut repair);
+        cache.migrate(version);
+        cache.file_mtime = modified_unix(path);
+        Ok((cache, repair))
     }
 
+    /// Writes the cache to `path`, creating its folder if needed.
     pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if self.version > CACHE_VERSION {
+            let message = format!(
+                "not overwriting cache format {} with older format {CACHE_VERSION}",
+                self.version
+            );
+            return Err(std::io::Error::new(std::io::ErrorKind::Unsupported, message));
+        }
         let data = serde_json::to_string(self)
             .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
         std::fs::write(path, data)
     }
+
+    /// How long ago the lookup cached under `key` was fetched, film or series.
+    /// Lookups saved without a timestamp are dated by the cache file's
+    /// modification time; `None` when that is unknown too, or nothing is
+    /// cached under `key`.
+    pub fn entry_age(&self, key: &str) -> Option<Duration> {
+        let fetched_at = match (self.entries.get(key), self.shows.get(key)) {
+            (Some(cached), _) => cached.fetched_at,
+            (None, Some(cached)) => cached.fetched_at,
+            (None, None) => return None,
+        };
+        let fetched_at = fetched_at.or(self.file_mtime)?;
+        Some(Duration::from_secs(unix_now().saturating_sub(fetched_at)))
+    }
+
+    /// Counts the cached lookups and finds the oldest and newest, going by
+    /// [`entry_age`](Self::entry_age).
+    pub fn info(&self) -> CacheInfo {
+        let mut info = CacheInfo {
+            movies: self.entries.len(),
+            shows: self.shows.len(),
+            ..CacheInfo::default()
+        };
+        let keys = self.entries.keys().chain(self.shows.keys());
+        for key in keys {
+            let Some(age) = self.entry_age(key) else {
+                info.undated += 1;
+                continue;
+            };
+            if info.oldest.as_ref().is_none_or(|(_, oldest)| age > *oldest) {
+                info.oldest = Some((key.clone(), age));
+            }
+            if info.newest.as_ref().is_none_or(|(_, newest)| age < *newest) {
+                info.newest = Some((key.clone(), age));
+            }
+        }
+        info
+    }
+
+    /// The score of the cached match for `key`, stored now if the cache was
+    /// written before scores were kept. `None` for misses.
+    fn match_confidence(&mut self, key: &str, title: &str) -> Option<f32> {
+        self.entries.get(key)?.value.as_ref()?;
+        let score = self.confidence_of(key, title);
+        self.confidence.entry(key.to_string()).or_insert(score);
+        Some(score)
+    }
+
+    /// The stored score for `key`, or one recomputed from the cached movie for
+    /// caches written before scores were kept. Misses score zero.
+    fn confidence_of(&self, key: &str, title: &str) -> f32 {
+        if let Some(score) = self.confidence.get(key) {
+            return *score;
+        }
+        match self.entries.get(key).and_then(|cached| cached.value.as_ref()) {
+            Some(movie) => movie.match_confidence(&lookup_title(title)),
+            None => 0.0,
+        }
+    }
+}
+
+/// Series names that should always be looked up as TV, even when a filename
+/// has no episode marker (`Fargo` the show rather than the film). Read from a
+/// plain text file with one title per line; blank lines and `#` comments are
+/// ignored.
+#[derive(Debug, Clone, Default)]
+pub struct ForceTvList {
+    titles: HashSet<String>,
+}
+
+impl ForceTvList {
+    /// An empty list when the file doesn't exist.
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Ok(Self::from_titles(
+                content
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#')),
+            )),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn from_titles<'a>(titles: impl IntoIterator<Item = &'a str>) -> Self {
+        Self {
+            titles: titles.into_iter().map(|title| cache_key(title, None)).collect(),
+        }
+    }
+
+    pub fn contains(&self, title: &str) -> bool {
+        self.titles.contains(&cache_key(title, None))
+    }
+}
+
+/// Titles mapped by hand to a TMDB movie id, for names the cleaner can't
+/// fix. Read from a user-edited JSON object such as `{"Amelie.2001.DVDRip": 194}`;
+/// a key matches an entry's raw or cleaned title, ignoring case.
+///
+/// Lookup precedence is override, then cache, then search: an overridden
+/// title is fetched by id like a `{tmdb-…}` hint (and cached under that id),
+/// so whatever search matched before is never consulted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TitleOverrides {
+    ids: HashMap<String, u32>,
+}
+
+impl TitleOverrides {
+    /// An empty map when the file doesn't exist.
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => {
+                let ids: HashMap<String, u32> = serde_json::from_str(&content).map_err(|err| {
+                    let message = format!("{}: {err}", path.display());
+                    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+                })?;
+                Ok(Self::from_ids(ids.iter().map(|(title, id)| (title.as_str(), *id))))
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// [`load`](Self::load) that never fails: a file that can't be read or
+    /// parsed gives no overrides and a message saying why, like
+    /// [`MovieCache::load_checked`].
+    pub fn load_checked(path: &Path) -> (Self, Option<String>) {
+        match Self::load(path) {
+            Ok(overrides) => (overrides, None),
+            Err(err) => (Self::default(), Some(format!("overrides were skipped: {err}"))),
+        }
+    }
+
+    pub fn from_ids<'a>(ids: impl IntoIterator<Item = (&'a str, u32)>) -> Self {
+        Self {
+            ids: ids.into_iter().map(|(title, id)| (cache_key(title, None), id)).collect(),
+        }
+    }
+
+    /// The overriding movie id for `entry`, by raw title first.
+    pub fn id_for(&self, entry: &WatchEntry) -> Option<u32> {
+        [&entry.raw_title, &entry.cleaned_title]
+            .into_iter()
+            .find_map(|title| self.ids.get(&cache_key(title, None)))
+            .copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+}
+
+/// Whether the cache could be used and kept, so callers can tell a failed
+/// save (worth retrying) from a damaged file (worth repairing).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CacheStatus {
+    #[default]
+    Ok,
+    /// The file isn't valid JSON and was ignored, and left as it was rather
+    /// than saved over; `goo cache repair` may recover it.
+    LoadCorrupted { path: PathBuf },
+    /// Written by a newer goo; read as far as possible and left unsaved.
+    NewerVersion { path: PathBuf, version: u32 },
+    /// Lookups worked but couldn't be written back, so they'll be repeated.
+    SaveFailed { path: PathBuf, reason: String },
+    /// Found to be unwritable before enriching; lookups were kept in memory only.
+    Unwritable { path: PathBuf, reason: String },
+}
+
+impl CacheStatus {
+    pub fn is_ok(&self) -> bool {
+        *self == CacheStatus::Ok
+    }
+
+    /// The status as a message, or `None` when there's nothing to report.
+    pub fn warning(&self) -> Option<String> {
+        (!self.is_ok()).then(|| self.to_string())
+    }
+}
+
+impl std::fmt::Display for CacheStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CacheStatus::Ok => write!(f, "cache ok"),
+            CacheStatus::LoadCorrupted { path } => write!(
+                f,
+                "cache {} is unreadable and was ignored; `goo cache repair` may recover it",
+                path.display()
+            ),
+            CacheStatus::NewerVersion { path, version } => write!(
+                f,
+                "cache {} was written by a newer goo (format {version}, this build reads {CACHE_VERSION}); \
+                 it was loaded as far as possible and will not be overwritten",
+                path.display()
+            ),
+            CacheStatus::SaveFailed { path, reason } => {
+                write!(f, "cache {} could not be saved: {reason}", path.display())
+            }
+            CacheStatus::Unwritable { path, reason } => write!(
+                f,
+                "cache {} is not writable ({reason}); this run's lookups were not saved",
+                path.display()
+            ),
+        }
+    }
+}
+
+/// What a cache holds; see [`MovieCache::info`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheInfo {
+    /// Film lookups, misses included.
+    pub movies: usize,
+    /// Series lookups, misses included.
+    pub shows: usize,
+    /// Lookups whose age is unknown.
+    pub undated: usize,
+    /// Cache key and age of the longest-held lookup.
+    pub oldest: Option<(String, Duration)>,
+    /// Cache key and age of the most recent lookup.
+    pub newest: Option<(String, Duration)>,
+}
+
+/// When `path` was last modified, in unix seconds.
+fn modified_unix(path: &Path) -> Option<u64> {
+    let modified = std::fs::metadata(path).and_then(|meta| meta.modified()).ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs())
+}
+
+/// What [`MovieCache::load_lenient`] kept and threw away, counted per map entry.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheRepair {
+    pub recovered: usize,
+    pub dropped: usize,
+}
+
+/// Parses `content`, or failing that the longest prefix that can be closed
+/// into valid JSON by cutting at a value boundary and adding the missing brackets.
+fn salvage_json(content: &str) -> Option<serde_json::Value> {
+    if let Ok(value) = serde_json::from_str(content) {
+        return Some(value);
+    }
+
+    let mut cuts: Vec<(usize, String)> = Vec::new();
+    let mut open = String::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    for (idx, ch) in content.char_indices() {
+        if in_string {
+            match ch {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match ch {
+            '"' => in_string = true,
+            '{' => open.push('}'),
+            '[' => open.push(']'),
+            '}' | ']' => {
+                open.pop();
+                cuts.push((idx + 1, open.clone()));
+            }
+            ',' => cuts.push((idx, open.clone())),
+            _ => {}
+        }
+    }
+
+    cuts.into_iter().rev().find_map(|(end, open)| {
+        let closers: String = open.chars().rev().collect();
+        serde_json::from_str(&format!("{}{closers}", &content[..end])).ok()
+    })
+}
+
+/// Deserializes a JSON object entry by entry, skipping the ones that don't fit.
+/// The format a cache file was written in: 1 predates the `version` field and
+/// 0 is a bare map of title lookups.
+fn stored_version(root: &serde_json::Map<String, serde_json::Value>) -> u32 {
+    match root.get("version").and_then(serde_json::Value::as_u64) {
+        Some(version) => version as u32,
+        None if root.contains_key("entries") => 1,
+        None => 0,
+    }
+}
+
+fn salvage_map<K, V>(value: Option<&serde_json::Value>, repair: &mut CacheRepair) -> HashMap<K, V>
+where
+    K: std::hash::Hash + Eq + serde::de::DeserializeOwned,
+    V: serde::de::DeserializeOwned,
+{
+    let mut salvaged = HashMap::new();
+    let Some(serde_json::Value::Object(map)) = value else {
+        return salvaged;
+    };
+    for (key, item) in map {
+        let single = serde_json::Value::Object([(key.clone(), item.clone())].into_iter().collect());
+        match serde_json::from_value::<HashMap<K, V>>(single) {
+            Ok(parsed) => {
+                salvaged.extend(parsed);
+                repair.recovered += 1;
+            }
+            Err(_) => repair.dropped += 1,
+        }
+    }
+    salvaged
+}
+
+/// Reads a JSON array of strings, skipping items that aren't strings.
+fn salvage_set(value: Option<&serde_json::Value>, repair: &mut CacheRepair) -> HashSet<String> {
+    let Some(serde_json::Value::Array(items)) = value else {
+        return HashSet::new();
+    };
+    let mut salvaged = HashSet::new();
+    for item in items {
+        match item.as_str() {
+            Some(key) => {
+                salvaged.insert(key.to_string());
+                repair.recovered += 1;
+            }
+            None => repair.dropped += 1,
+        }
+    }
+    salvaged
+}
+
+/// How entries are enriched: which poster size to link, when a match counts
+/// as shaky, and any titles the user has matched by hand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnrichOptions {
+    poster_size: String,
+    confidence_threshold: f32,
+    overrides: TitleOverrides,
+}
+
+impl Default for EnrichOptions {
+    fn default() -> Self {
+        Self {
+            poster_size: DEFAULT_POSTER_SIZE.to_string(),
+            confidence_threshold: DEFAULT_REFINE_THRESHOLD,
+            overrides: TitleOverrides::default(),
+        }
+    }
+}
+
+impl EnrichOptions {
+    /// Poster width for `poster_url`, one of [`POSTER_SIZES`] (e.g. `"w780"`
+    /// for a detail view). Unknown sizes fall back to [`DEFAULT_POSTER_SIZE`].
+    pub fn with_poster_size(mut self, size: &str) -> Self {
+        let size = size.trim();
+        self.poster_size = if POSTER_SIZES.contains(&size) {
+            size.to_string()
+        } else {
+            DEFAULT_POSTER_SIZE.to_string()
+        };
+        self
+    }
+
+    pub fn poster_size(&self) -> &str {
+        &self.poster_size
+    }
+
+    /// Matches scoring below `threshold` are marked `low_confidence`.
+    /// Defaults to [`DEFAULT_REFINE_THRESHOLD`].
+    pub fn with_confidence_threshold(mut self, threshold: f32) -> Self {
+        self.confidence_threshold = threshold;
+        self
+    }
+
+    pub fn confidence_threshold(&self) -> f32 {
+        self.confidence_threshold
+    }
+
+    /// Fetch these titles by id instead of looking them up; see [`TitleOverrides`].
+    pub fn with_overrides(mut self, overrides: TitleOverrides) -> Self {
+        self.overrides = overrides;
+        self
+    }
 }
 
+/// Matches every entry through `client`: a [`TmdbClient`], or any other
+/// [`MovieSource`].
 pub fn enrich_entries(
     entries: Vec<WatchEntry>,
-    client: &TmdbClient,
+    client: &impl MovieSource,
+    cache: &mut MovieCache,
+    options: &EnrichOptions,
+) -> Result<Vec<EnrichedEntry>, TmdbError> {
+    enrich_entries_routed(entries, client, cache, &ForceTvList::default(), options, |_| {})
+}
+
+/// [`enrich_entries`] that hands each entry to `on_entry` as soon as it is
+/// enriched, so callers can aggregate without a second pass.
+pub fn enrich_entries_observed(
+    entries: Vec<WatchEntry>,
+    client: &impl MovieSource,
+    cache: &mut MovieCache,
+    on_entry: impl FnMut(&EnrichedEntry),
+) -> Result<Vec<EnrichedEntry>, TmdbError> {
+    let options = EnrichOptions::default();
+    enrich_entries_routed(entries, client, cache, &ForceTvList::default(), &options, on_entry)
+}
+
+/// [`enrich_entries_observed`] that also sends titles on `force_tv` to TV
+/// search. Episodes always go to TV search.
+pub fn enrich_entries_routed(
+    entries: Vec<WatchEntry>,
+    client: &impl MovieSource,
     cache: &mut MovieCache,
+    force_tv: &ForceTvList,
+    options: &EnrichOptions,
+    mut on_entry: impl FnMut(&EnrichedEntry),
 ) -> Result<Vec<EnrichedEntry>, TmdbError> {
     let mut enriched = Vec::with_capacity(entries.len());
     for entry in entries {
-        let key = cache_key(&entry.cleaned_title, entry.release_year);
-        let movie = if key.is_empty() {
-            None
-        } else if let Some(cached) = cache.entries.get(&key) {
-            cached.clone()
+        let entry = enrich_one(entry, client, cache, force_tv, options)?;
+        on_entry(&entry);
+        enriched.push(entry);
+    }
+    Ok(enriched)
+}
+
+/// [`enrich_entries`] that carries on past failed lookups instead of
+/// stopping at the first one. Every entry comes back; those whose lookup
+/// failed are unmatched, with [`EnrichedEntry::error`] set, and each failure
+/// is also returned with its title. Successful lookups are cached as usual.
+pub fn enrich_entries_lenient(
+    entries: Vec<WatchEntry>,
+    client: &impl MovieSource,
+    cache: &mut MovieCache,
+    options: &EnrichOptions,
+) -> (Vec<EnrichedEntry>, Vec<(String, TmdbError)>) {
+    let force_tv = ForceTvList::default();
+    let mut enriched = Vec::with_capacity(entries.len());
+    let mut failures = Vec::new();
+    for entry in entries {
+        let fallback = entry.clone();
+        match enrich_one(entry, client, cache, &force_tv, options) {
+            Ok(entry) => enriched.push(entry),
+            Err(error) => {
+                let mut entry =
+                    EnrichedEntry::from_watch(fallback, None, None, None, client, options);
+                entry.error = Some(error.to_string());
+                failures.push((entry.cleaned_title.clone(), error));
+                enriched.push(entry);
+            }
+        }
+    }
+    (enriched, failures)
+}
+
+/// Looks one entry up through the cache, recording whatever gets fetched.
+fn enrich_one(
+    entry: WatchEntry,
+    client: &impl MovieSource,
+    cache: &mut MovieCache,
+    force_tv: &ForceTvList,
+    options: &EnrichOptions,
+) -> Result<EnrichedEntry, TmdbError> {
+    let hint = direct_hint(&entry, &options.overrides);
+    let key = entry_key(&entry, hint.as_ref());
+    // Live streams have no real title, so searching would only burn API calls.
+    let (movie, show) = if key.is_empty() || entry.is_stream {
+        (None, None)
+    } else if let Some(hint) = &hint {
+        let movie = match cache.usable_movie(&key, client) {
+            Some(cached) => cached.clone(),
+            None => {
+                let fetched = find_hinted(client, hint)?;
+                record_match(cache, key.clone(), fetched.clone());
+                fetched.map(|(movie, _)| movie)
+            }
+        };
+        (movie, None)
+    } else if is_tv_lookup(&entry, None, force_tv) {
+        let show = match cache.fresh_show(&key) {
+            Some(cached) => cached.clone(),
+            None => {
+                let title = lookup_title(&entry.cleaned_title);
+                let results = client.search_tv(&title, entry.release_year)?;
+                let fetched = results.into_iter().next();
+                cache.shows.insert(key.clone(), Cached::now(fetched.clone()));
+                fetched
+            }
+        };
+        (None, show)
+    } else if let Some(cached) = cache.usable_movie(&key, client) {
+        (cached.clone(), None)
+    } else {
+        let fetched = find_match(client, &entry.cleaned_title, entry.release_year)?;
+        record_match(cache, key.clone(), fetched.clone());
+        (fetched.map(|(movie, _)| movie), None)
+    };
+
+    let confidence = match movie {
+        Some(_) => cache.match_confidence(&key, &entry.cleaned_title),
+        None => None,
+    };
+    Ok(EnrichedEntry::from_watch(entry, movie, show, confidence, client, options))
+}
+
+/// Whether `entry` is searched as a TV show: an episode, or a title on
+/// `force_tv`, unless it has an id to fetch a movie by.
+fn is_tv_lookup(entry: &WatchEntry, hint: Option<&IdHint>, force_tv: &ForceTvList) -> bool {
+    hint.is_none() && (entry.episode.is_some() || force_tv.contains(&entry.cleaned_title))
+}
+
+/// [`enrich_entries_routed`] with uncached lookups spread over up to
+/// `concurrency` threads. Titles already in the cache spawn no work, and the
+/// output keeps the order of `entries`. Lookups are staged and only written
+/// to `cache` once all of them succeed, so a failed request leaves the cache
+/// as it was.
+pub fn enrich_entries_parallel(
+    entries: Vec<WatchEntry>,
+    client: &(impl MovieSource + Sync),
+    cache: &mut MovieCache,
+    force_tv: &ForceTvList,
+    options: &EnrichOptions,
+    concurrency: usize,
+) -> Result<Vec<EnrichedEntry>, TmdbError> {
+    let mut pending = Vec::new();
+    let mut queued = HashSet::new();
+    for entry in &entries {
+        let hint = direct_hint(entry, &options.overrides);
+        let key = entry_key(entry, hint.as_ref());
+        if key.is_empty() || entry.is_stream {
+            continue;
+        }
+        let tv = is_tv_lookup(entry, hint.as_ref(), force_tv);
+        let cached = if tv {
+            cache.fresh_show(&key).is_some()
         } else {
-            let fetched = client.best_match(&entry.cleaned_title, entry.release_year)?;
-            cache.entries.insert(key, fetched.clone());
-            fetched
+            cache.usable_movie(&key, client).is_some()
         };
+        if !cached && queued.insert((key.clone(), tv)) {
+            pending.push(PendingLookup {
+                key,
+                title: entry.cleaned_title.clone(),
+                year: entry.release_year,
+                tv,
+                hint,
+            });
+        }
+    }
 
-        enriched.push(EnrichedEntry::from_watch(entry, movie));
+    let fetched = run_lookups(&pending, client, concurrency)?;
+    for (lookup, result) in pending.into_iter().zip(fetched) {
+        match result {
+            Fetched::Show(show) => {
+                cache.shows.insert(lookup.key, Cached::now(show));
+            }
+            Fetched::Movie(found) => record_match(cache, lookup.key, found),
+        }
     }
+
+    let enriched = entries
+        .into_iter()
+        .map(|entry| {
+            let hint = direct_hint(&entry, &options.overrides);
+            let key = entry_key(&entry, hint.as_ref());
+            let (movie, show) = if key.is_empty() || entry.is_stream {
+                (None, None)
+            } else if is_tv_lookup(&entry, hint.as_ref(), force_tv) {
+                (None, cache.shows.get(&key).and_then(|cached| cached.value.clone()))
+            } else {
+                (cache.entries.get(&key).and_then(|cached| cached.value.clone()), None)
+            };
+            let confidence = match movie {
+                Some(_) => cache.match_confidence(&key, &entry.cleaned_title),
+                None => None,
+            };
+            EnrichedEntry::from_watch(entry, movie, show, confidence, client, options)
+        })
+        .collect();
     Ok(enriched)
 }
 
-fn cache_key(title: &str, year: Option<i32>) -> String {
-    let mut key = title.trim().to_lowercase();
-    if let Some(year) = year {
-        key.push('|');
-        key.push_str(&year.to_string());
+/// One uncached title queued by [`enrich_entries_parallel`].
+struct PendingLookup {
+    key: String,
+    title: String,
+    year: Option<i32>,
+    tv: bool,
+    hint: Option<IdHint>,
+}
+
+enum Fetched {
+    Movie(Option<(TmdbMovie, f32)>),
+    Show(Option<TmdbTvShow>),
+}
+
+impl PendingLookup {
+    fn fetch(&self, client: &impl MovieSource) -> Result<Fetched, TmdbError> {
+        if let Some(hint) = &self.hint {
+            find_hinted(client, hint).map(Fetched::Movie)
+        } else if self.tv {
+            let results = client.search_tv(&lookup_title(&self.title), self.year)?;
+            Ok(Fetched::Show(results.into_iter().next()))
+        } else {
+            find_match(client, &self.title, self.year).map(Fetched::Movie)
+        }
     }
-    key
 }
 
-impl EnrichedEntry {
-    fn from_watch(entry: WatchEntry, movie: Option<TmdbMovie>) -> Self {
-        let tmdb_url = movie.as_ref().map(|item| item.tmdb_url());
-        let poster_url = movie
-            .as_ref()
-            .and_then(|item| item.poster_url(DEFAULT_POSTER_SIZE));
-        Self {
-            watched_at: entry.watched_at,
-            raw_title: entry.raw_title,
-            cleaned_title: entry.cleaned_title,
-            release_year: entry.release_year,
-            movie,
-            tmdb_url,
-            poster_url,
+/// Runs `lookups` on a pool of worker threads pulling from a shared index and
+/// returns the results in the same order. Workers stop picking up new
+/// lookups once any of them fails, and the first error is returned.
+fn run_lookups(
+    lookups: &[PendingLookup],
+    client: &(impl MovieSource + Sync),
+    concurrency: usize,
+) -> Result<Vec<Fetched>, TmdbError> {
+    if lookups.is_empty() {
+        return Ok(Vec::new());
+    }
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let workers = concurrency.clamp(1, lookups.len());
+
+    let outcomes: Vec<Result<Vec<(usize, Fetched)>, TmdbError>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    while !failed.load(Ordering::Relaxed) {
+                        let idx = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(lookup) = lookups.get(idx) else {
+                            break;
+                        };
+                        match lookup.fetch(client) {
+                            Ok(fetched) => done.push((idx, fetched)),
+                            Err(err) => {
+                                failed.store(true, Ordering::Relaxed);
+                                return Err(err);
+                            }
+                        }
+                    }
+                    Ok(done)
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("lookup worker panicked"))
+            .collect()
+    });
+
+    let mut slots: Vec<Option<Fetched>> = lookups.iter().map(|_| None).collect();
+    for outcome in outcomes {
+        for (idx, fetched) in outcome? {
+            slots[idx] = Some(fetched);
+        }
+    }
+    Ok(slots
+        .into_iter()
+        .map(|slot| slot.expect("every lookup ran"))
+        .collect())
+}
+
+/// Caches a movie lookup along with its score; a miss forgets any old score
+/// and counts towards the miss backoff, which a hit resets.
+fn record_match(cache: &mut MovieCache, key: String, found: Option<(TmdbMovie, f32)>) {
+    match found.as_ref() {
+        Some((_, score)) => {
+            cache.confidence.insert(key.clone(), *score);
+            cache.misses.remove(&key);
+        }
+        None => {
+            cache.confidence.remove(&key);
+            *cache.misses.entry(key.clone()).or_default() += 1;
         }
     }
+    cache.entries.insert(key, Cached::now(found.map(|(movie, _)| movie)));
+}
+
+/// The id to fetch by instead of searching, if any: a user override, else an
+/// id hint from the filename. Hints name movies, so episodes ignore them; an
+/// override is the user's call and applies either way.
+fn direct_hint(entry: &WatchEntry, overrides: &TitleOverrides) -> Option<IdHint> {
+    if let Some(id) = overrides.id_for(entry) {
+        return Some(IdHint::Tmdb(id));
+    }
+    entry.id_hint.clone().filter(|_| entry.episode.is_none())
+}
+
+/// The cache key an entry is stored under. Entries fetched by id get their
+/// own keys, so `Crash {imdb-tt0375679}` and a plain `Crash` don't share a match.
+fn entry_key(entry: &WatchEntry, hint: Option<&IdHint>) -> String {
+    match hint {
+        Some(IdHint::Tmdb(id)) => format!("tmdb:{id}"),
+        Some(IdHint::Imdb(id)) => format!("imdb:{id}"),
+        // A year alone is nothing to search for.
+        None if entry.cleaned_title.trim().is_empty() => String::new(),
+        None => cache_key(&entry.cleaned_title, entry.release_year),
+    }
+}
+
+/// Fetches exactly the movie a hint names. An id match is certain, so it
+/// scores a full 1.0.
+fn find_hinted(
+    client: &impl MovieSource,
+    hint: &IdHint,
+) -> Result<Option<(TmdbMovie, f32)>, TmdbError> {
+    let movie = match hint {
+        IdHint::Tmdb(id) => client.movie_by_id(*id)?,
+        IdHint::Imdb(id) => client.find_by_imdb(id)?,
+    };
+    Ok(movie.map(|movie| (movie, 1.0)))
+}
+
+/// Walks the fallback chain until a query returns something, scoring the hit
+/// against the query that found it.
+fn find_match(
+    client: &impl MovieSource,
+    title: &str,
+    year: Option<i32>,
+) -> Result<Option<(TmdbMovie, f32)>, TmdbError> {
+    for query in search_variants(&lookup_title(title)) {
+        if let Some(movie) = client.best_match(&query, year)? {
+            let score = movie.match_confidence(&query);
+            return Ok(Some((movie, score)));
+        }
+    }
+    Ok(None)
+}
+
+/// Re-searches cached entries that matched poorly (or not at all), leaving
+/// confident matches untouched. Each fallback query picks its result like
+/// [`TmdbClient::best_match_scored`], and the best of those replaces the cached
+/// one if it beats the old score. Scores are compared and stored as
+/// [`TmdbMovie::match_confidence`], the title-only scale enrichment uses, so a
+/// year bonus never makes the same match look better. Entries fetched by id
+/// (an override or an id hint) and episodes are never re-searched. Returns how
+/// many cache entries improved.
+pub fn refine_entries(
+    entries: &[WatchEntry],
+    client: &impl MovieSource,
+    cache: &mut MovieCache,
+    overrides: &TitleOverrides,
+    threshold: f32,
+) -> Result<usize, TmdbError> {
+    let mut seen = HashSet::new();
+    let mut improved = 0;
+    for entry in entries {
+        if entry.is_stream || entry.episode.is_some() {
+            continue;
+        }
+        if direct_hint(entry, overrides).is_some() {
+            continue;
+        }
+        let key = entry_key(entry, None);
+        if key.is_empty() || !seen.insert(key.clone()) {
+            continue;
+        }
+        // Uncached titles get a regular lookup during enrichment.
+        if !cache.entries.contains_key(&key) || cache.pinned.contains(&key) {
+            continue;
+        }
+        let current = cache.confidence_of(&key, &entry.cleaned_title);
+        if current >= threshold {
+            continue;
+        }
+
+        let mut best: Option<(TmdbMovie, f32)> = None;
+        for query in search_variants(&lookup_title(&entry.cleaned_title)) {
+            if let Some((movie, _)) = client.best_match_scored(&query, entry.release_year)? {
+                let score = movie.match_confidence(&query);
+                if best.as_ref().is_none_or(|(_, best_score)| score > *best_score) {
+                    best = Some((movie, score));
+                }
+            }
+        }
+
+        if let Some((movie, score)) = best {
+            if score > current {
+                cache.entries.insert(key.clone(), Cached::now(Some(movie)));
+                cache.misses.remove(&key);
+                cache.confidence.insert(key, score);
+                improved += 1;
+            }
+        }
+    }
+    Ok(improved)
+}
+
+/// The form of a title that is searched for and cached under: a trailing
+/// article moved back to the front ("Matrix, The" -> "The Matrix") and
+/// trailing punctuation dropped. Displayed titles keep the file's spelling.
+pub fn lookup_title(title: &str) -> String {
+    let title = title
+        .trim()
+        .trim_end_matches(['.', ',', ';', ':', '!', '?', '-'])
+        .trim_end();
+    if let Some((rest, article)) = title.rsplit_once(',') {
+        let rest = rest.trim();
+        for candidate in ["The", "A", "An"] {
+            if !rest.is_empty() && article.trim().eq_ignore_ascii_case(candidate) {
+                return format!("{candidate} {rest}");
+            }
+        }
+    }
+    title.to_string()
+}
+
+/// The fallback chain of queries tried for a title, most literal first.
+pub fn search_variants(title: &str) -> Vec<String> {
+    let title = title.trim();
+    let mut variants = vec![title.to_string()];
+    let mut push = |candidate: String| {
+        let candidate = candidate.trim().to_string();
+        if !candidate.is_empty() && !variants.contains(&candidate) {
+            variants.push(candidate);
+        }
+    };
+
+    let lower = title.to_lowercase();
+    for article in ["the ", "a ", "an "] {
+        if lower.starts_with(article) {
+            push(title[article.len()..].to_string());
+        }
+    }
+    if title.contains('&') {
+        push(title.replace('&', "and"));
+    }
+    if lower.contains(" and ") {
+        push(replace_word(title, "and", "&"));
+    }
+
+    // Renamers disagree on "John Wick Chapter 4" vs "John Wick 4"; try the other form.
+    let tokens: Vec<&str> = title.split_whitespace().collect();
+    if let Some(chapter) = tokens
+        .iter()
+        .position(|token| token.eq_ignore_ascii_case("chapter"))
+        .filter(|idx| tokens.get(idx + 1).is_some_and(|next| is_sequel_number(next)))
+    {
+        let mut shortened = tokens.clone();
+        shortened.remove(chapter);
+        push(shortened.join(" "));
+    } else if let Some((last, rest)) = tokens.split_last() {
+        if !rest.is_empty() && is_sequel_number(last) {
+            push(format!("{} Chapter {last}", rest.join(" ")));
+        }
+    }
+    // TMDB sometimes indexes only the unaccented spelling; try it last.
+    if let Some(folded) = ascii_fold(title) {
+        push(folded);
+    }
+    variants
+}
+
+fn is_sequel_number(token: &str) -> bool {
+    (1..=2).contains(&token.len())
+        && token.chars().all(|ch| ch.is_ascii_digit())
+        && token != "0"
+}
+
+fn replace_word(title: &str, word: &str, replacement: &str) -> String {
+    title
+        .split(' ')
+        .map(|token| if token.eq_ignore_ascii_case(word) { replacement } else { token })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Fetches runtime and genres for every matched entry, one request per distinct movie.
+pub fn attach_details(
+    entries: &mut [EnrichedEntry],
+    client: &TmdbClient,
+    cache: &mut MovieCache,
+) -> Result<(), TmdbError> {
+    for entry in entries.iter_mut() {
+        let Some(id) = entry.movie.as_ref().map(|movie| movie.id) else {
+            continue;
+        };
+        let details = match cache.details.get(&id) {
+            Some(cached) => cached.clone(),
+            None => {
+                let fetched = client.movie_details(id)?;
+                cache.details.insert(id, fetched.clone());
+                fetched
+            }
+        };
+        entry.details = Some(details);
+    }
+    Ok(())
+}
+
+/// Every watch of one title, as combined by [`aggregate_watches`].
+#[derive(Debug, Clone, Serialize)]
+pub struct AggregatedEntry {
+    pub cleaned_title: String,
+    pub release_year: Option<i32>,
+    /// The match shared by every watch; the first one found if they differ.
+    pub movie: Option<TmdbMovie>,
+    pub watch_count: usize,
+    /// Earliest and latest `watched_at`, as logged. `None` when no watch of
+    /// the title has a timestamp.
+    pub first_watched: Option<String>,
+    pub last_watched: Option<String>,
+}
+
+/// Combines rewatches of the same title and year (ignoring case and accents)
+/// into one entry with a watch count, most recently watched first. Titles
+/// never watched with a timestamp come last, in log order. Episodes combine
+/// under their series.
+pub fn aggregate_watches(entries: &[EnrichedEntry]) -> Vec<AggregatedEntry> {
+    let mut order: Vec<String> = Vec::new();
+    let mut groups: HashMap<String, (AggregatedEntry, Option<i64>, Option<i64>)> = HashMap::new();
+    for entry in entries {
+        let key = cache_key(&entry.cleaned_title, entry.release_year);
+        let (group, first, last) = groups.entry(key.clone()).or_insert_with(|| {
+            order.push(key);
+            let group = AggregatedEntry {
+                cleaned_title: entry.cleaned_title.clone(),
+                release_year: entry.release_year,
+                movie: None,
+                watch_count: 0,
+                first_watched: None,
+                last_watched: None,
+            };
+            (group, None, None)
+        });
+        group.watch_count += 1;
+        if group.movie.is_none() {
+            group.movie = entry.movie.clone();
+        }
+        let watched = entry.watched_at.as_deref();
+        let Some(unix) = watched.and_then(parse_timestamp).map(|watched| watched.to_unix()) else {
+            continue;
+        };
+        if first.is_none_or(|first| unix < first) {
+            *first = Some(unix);
+            group.first_watched = watched.map(str::to_string);
+        }
+        if last.is_none_or(|last| unix > last) {
+            *last = Some(unix);
+            group.last_watched = watched.map(str::to_string);
+        }
+    }
+
+    let mut aggregated: Vec<(AggregatedEntry, Option<i64>)> = order
+        .into_iter()
+        .filter_map(|key| groups.remove(&key))
+        .map(|(group, _, last)| (group, last))
+        .collect();
+    aggregated.sort_by_key(|(_, last)| std::cmp::Reverse(*last));
+    aggregated.into_iter().map(|(group, _)| group).collect()
+}
+
+/// The [`CollectionGroup`] name for matched films outside any collection.
+pub const UNGROUPED_COLLECTION: &str = "Ungrouped";
+
+/// Matched films watched from one TMDB collection, as counted by
+/// [`group_by_collection`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CollectionGroup {
+    /// The TMDB collection id; `None` for [`UNGROUPED_COLLECTION`].
+    pub id: Option<u32>,
+    pub name: String,
+    /// Distinct films watched from the collection.
+    pub films: usize,
+    /// Every watch of those films, rewatches included.
+    pub watches: usize,
+}
+
+/// Buckets matched films by TMDB collection, most films watched first, with
+/// films outside a collection, or whose details weren't fetched (see
+/// [`attach_details`]), in a final [`UNGROUPED_COLLECTION`] bucket. Unmatched
+/// entries and series are left out.
+pub fn group_by_collection(entries: &[EnrichedEntry]) -> Vec<CollectionGroup> {
+    let mut groups: HashMap<Option<u32>, (CollectionGroup, HashSet<u32>)> = HashMap::new();
+    for entry in entries {
+        let Some(movie) = entry.movie.as_ref() else {
+            continue;
+        };
+        let collection = entry.details.as_ref().and_then(|details| details.collection.as_ref());
+        let id = collection.map(|collection| collection.id);
+        let (group, films) = groups.entry(id).or_insert_with(|| {
+            let name = collection.map_or(UNGROUPED_COLLECTION, |collection| &collection.name);
+            let group = CollectionGroup { id, name: name.to_string(), films: 0, watches: 0 };
+            (group, HashSet::new())
+        });
+        group.watches += 1;
+        if films.insert(movie.id) {
+            group.films += 1;
+        }
+    }
+
+    let mut groups: Vec<CollectionGroup> = groups.into_values().map(|(group, _)| group).collect();
+    groups.sort_by(|a, b| {
+        a.id.is_none()
+            .cmp(&b.id.is_none())
+            .then_with(|| b.films.cmp(&a.films))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    groups
+}
+
+/// Fetches TMDB keywords for every matched entry, one request per distinct movie.
+pub fn attach_keywords(
+    entries: &mut [EnrichedEntry],
+    client: &TmdbClient,
+    cache: &mut MovieCache,
+) -> Result<(), TmdbError> {
+    for entry in entries.iter_mut() {
+        let Some(id) = entry.movie.as_ref().map(|movie| movie.id) else {
+            continue;
+        };
+        let keywords = match cache.keywords.get(&id) {
+            Some(cached) => cached.clone(),
+            None => {
+                let fetched = client.keywords(id)?;
+                cache.keywords.insert(id, fetched.clone());
+                fetched
+            }
+        };
+        entry.keywords = keywords;
+    }
+    Ok(())
+}
+
+/// Fetches the directors of every matched film from its credits, one request
+/// per distinct movie. Films with no director listed keep an empty list.
+pub fn attach_directors(
+    entries: &mut [EnrichedEntry],
+    client: &TmdbClient,
+    cache: &mut MovieCache,
+) -> Result<(), TmdbError> {
+    for entry in entries.iter_mut() {
+        let Some(id) = entry.movie.as_ref().map(|movie| movie.id) else {
+            continue;
+        };
+        let directors = match cache.directors.get(&id) {
+            Some(cached) => cached.clone(),
+            None => {
+                let fetched = client.directors(id)?;
+                cache.directors.insert(id, fetched.clone());
+                fetched
+            }
+        };
+        entry.directors = directors;
+    }
+    Ok(())
+}
+
+/// Sets `imdb_url` on every matched film, one external-ids request per
+/// distinct movie. An `{imdb-…}` hint in the filename is used as is.
+pub fn attach_imdb_ids(
+    entries: &mut [EnrichedEntry],
+    client: &TmdbClient,
+    cache: &mut MovieCache,
+) -> Result<(), TmdbError> {
+    for entry in entries.iter_mut() {
+        let Some(id) = entry.movie.as_ref().map(|movie| movie.id) else {
+            continue;
+        };
+        if let Some(IdHint::Imdb(imdb_id)) = parse_id_hint(&entry.raw_title) {
+            cache.imdb_ids.insert(id, Some(imdb_id));
+        }
+        let imdb_id = match cache.imdb_ids.get(&id) {
+            Some(cached) => cached.clone(),
+            None => {
+                let fetched = client.external_ids(id)?.imdb_id;
+                cache.imdb_ids.insert(id, fetched.clone());
+                fetched
+            }
+        };
+        entry.imdb_url = imdb_id.as_deref().map(imdb_url);
+    }
+    Ok(())
+}
+
+/// Fills blank overviews from the client's language fallback chain (see
+/// [`TmdbClient::fallback_overview`]), one translations request per distinct
+/// movie. Films with no overview in any of those languages stay blank.
+pub fn attach_overviews(
+    entries: &mut [EnrichedEntry],
+    client: &TmdbClient,
+    cache: &mut MovieCache,
+) -> Result<(), TmdbError> {
+    for entry in entries.iter_mut() {
+        let Some(movie) = entry.movie.as_mut() else {
+            continue;
+        };
+        if movie.overview.as_deref().is_some_and(|overview| !overview.trim().is_empty()) {
+            continue;
+        }
+        let overview = match cache.overviews.get(&movie.id) {
+            Some(cached) => cached.clone(),
+            None => {
+                let fetched = client.fallback_overview(movie)?.unwrap_or_default();
+                cache.overviews.insert(movie.id, fetched.clone());
+                fetched
+            }
+        };
+        if !overview.is_empty() {
+            movie.overview = Some(overview);
+        }
+    }
+    Ok(())
+}
+
+/// What [`download_posters`] did with the posters it was given.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PosterDownloads {
+    pub fetched: usize,
+    /// Already on disk from an earlier run, so not downloaded again.
+    pub cached: usize,
+    /// `(url, reason)` per poster that couldn't be saved; its entry keeps the remote URL.
+    pub failed: Vec<(String, String)>,
+}
+
+/// Upper bound on one poster download, well above TMDB's `original` size.
+const MAX_POSTER_BYTES: u64 = 20 * 1024 * 1024;
+
+/// Saves every remote `poster_url` under `dir` as `<tmdb id>-<size>.jpg`
+/// (`tv-<id>-<size>.jpg` for series) and points the entry at the local file, so
+/// posters survive offline and aren't refetched on every render. Files that
+/// already exist are reused. A poster that fails to download is recorded and
+/// skipped; only failing to create `dir` aborts the batch. Downloads go
+/// through `client`'s connections, so they use its proxy and timeout.
+pub fn download_posters(
+    entries: &mut [EnrichedEntry],
+    client: &TmdbClient,
+    dir: &Path,
+) -> std::io::Result<PosterDownloads> {
+    std::fs::create_dir_all(dir)?;
+    let mut summary = PosterDownloads::default();
+    for entry in entries.iter_mut() {
+        let Some(url) = entry.poster_url.clone() else {
+            continue;
+        };
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            continue;
+        }
+        let name = match (&entry.movie, &entry.show) {
+            (Some(movie), _) => movie.id.to_string(),
+            (None, Some(show)) => format!("tv-{}", show.id),
+            (None, None) => continue,
+        };
+        let mut segments = url.rsplit('/');
+        let extension = segments
+            .next()
+            .and_then(|file| file.rsplit_once('.'))
+            .map(|(_, extension)| extension)
+            .filter(|extension| extension.chars().all(|ch| ch.is_ascii_alphanumeric()))
+            .unwrap_or("jpg");
+        // Sizes get their own files, so a new poster size is downloaded afresh.
+        let path = match segments.next().filter(|size| POSTER_SIZES.contains(size)) {
+            Some(size) => dir.join(format!("{name}-{size}.{extension}")),
+            None => dir.join(format!("{name}.{extension}")),
+        };
+
+        if path.is_file() {
+            summary.cached += 1;
+        } else if let Err(reason) = fetch_poster(client.agent(), &url, &path) {
+            summary.failed.push((url, reason));
+            continue;
+        } else {
+            summary.fetched += 1;
+        }
+        entry.poster_url = Some(path.display().to_string());
+    }
+    Ok(summary)
+}
+
+/// Downloads `url` to `path` through a temporary file, so an interrupted
+/// download never passes for a cached poster.
+fn fetch_poster(agent: &ureq::Agent, url: &str, path: &Path) -> Result<(), String> {
+    let response = agent.get(url).call().map_err(|err| err.to_string())?;
+    let mut bytes = Vec::new();
+    response
+        .into_reader()
+        .take(MAX_POSTER_BYTES)
+        .read_to_end(&mut bytes)
+        .map_err(|err| err.to_string())?;
+    let partial = path.with_extension("part");
+    std::fs::write(&partial, &bytes).map_err(|err| err.to_string())?;
+    std::fs::rename(&partial, path).map_err(|err| err.to_string())
+}
+
+/// The [`lookup_title`], lowercased, accent-folded and whitespace-collapsed,
+/// so spellings that search alike share one cache entry.
+pub(crate) fn cache_key(title: &str, year: Option<i32>) -> String {
+    let title = lookup_title(title).split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut key = ascii_fold(&title).unwrap_or(title).to_lowercase();
+    if let Some(year) = year {
+        key.push('|');
+        key.push_str(&year.to_string());
+    }
+    key
+}
+
+/// The current [`cache_key`] for a key written by an older one.
+fn recompute_key(key: &str) -> String {
+    match key.rsplit_once('|') {
+        Some((title, year)) => match year.parse() {
+            Ok(year) => cache_key(title, Some(year)),
+            Err(_) => cache_key(key, None),
+        },
+        None => cache_key(key, None),
+    }
+}
+
+/// A parsed release year that disagrees with the matched movie's year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct YearConflict {
+    pub parsed: i32,
+    pub matched: i32,
+}
+
+impl EnrichedEntry {
+    /// Set when the year taken from the filename differs from the matched
+    /// movie's, which usually means the cleaner took part of the title
+    /// ("Blade Runner 2049", "2046") as the release year.
+    pub fn year_conflict(&self) -> Option<YearConflict> {
+        let parsed = self.release_year?;
+        let matched = self.movie.as_ref()?.release_year()?;
+        (parsed != matched).then_some(YearConflict { parsed, matched })
+    }
+
+    /// Replaces a conflicting release year with the matched movie's, returning
+    /// the conflict that was corrected.
+    pub fn correct_release_year(&mut self) -> Option<YearConflict> {
+        let conflict = self.year_conflict()?;
+        self.release_year = Some(conflict.matched);
+        Some(conflict)
+    }
+
+    pub(crate) fn from_watch(
+        entry: WatchEntry,
+        movie: Option<TmdbMovie>,
+        show: Option<TmdbTvShow>,
+        confidence: Option<f32>,
+        client: &impl MovieSource,
+        options: &EnrichOptions,
+    ) -> Self {
+        let poster_size = options.poster_size();
+        let movie = movie.map(|mut movie| {
+            if !client.includes_raw() {
+                movie.raw = None;
+            }
+            movie
+        });
+        let tmdb_url = match (&movie, &show) {
+            (Some(item), _) => Some(item.tmdb_url()),
+            (None, Some(series)) => Some(series.tmdb_url()),
+            (None, None) => None,
+        };
+        let poster_url = match (&movie, &show) {
+            (Some(item), _) => client.poster_url(item, poster_size),
+            (None, Some(series)) => client.show_poster_url(series, poster_size),
+            (None, None) => None,
+        };
+        Self {
+            watched_at: entry.watched_at,
+            raw_title: entry.raw_title,
+            source_path: entry.source_path,
+            cleaned_title: entry.cleaned_title,
+            release_year: entry.release_year,
+            is_stream: entry.is_stream,
+            episode: entry.episode,
+            movie,
+            show,
+            confidence,
+            low_confidence: confidence.is_some_and(|score| score < options.confidence_threshold),
+            error: None,
+            tmdb_url,
+            imdb_url: None,
+            poster_url,
+            details: None,
+            keywords: Vec::new(),
+            directors: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{mock_server, watch, MockMovieSource};
+
+    fn movie(id: u32, title: &str) -> TmdbMovie {
+        TmdbMovie {
+            id,
+            title: title.to_string(),
+            original_title: None,
+            original_language: None,
+            overview: None,
+            release_date: None,
+            poster_path: None,
+            backdrop_path: None,
+            raw: None,
+        }
+    }
+
+    fn matched_entry(
+        entry: WatchEntry,
+        movie: Option<TmdbMovie>,
+        client: &TmdbClient,
+    ) -> EnrichedEntry {
+        EnrichedEntry::from_watch(entry, movie, None, None, client, &EnrichOptions::default())
+    }
+
+    #[test]
+    fn corrects_release_years_that_conflict_with_the_match() {
+        let client = TmdbClient::new("key");
+        let mut matched = movie(530915, "1917");
+        matched.release_date = Some("2019-12-25".to_string());
+        let parsed = crate::parse_log_line("1917.2019.1080p.mkv").expect("entry");
+        let entry = matched_entry(parsed, Some(matched.clone()), &client);
+        assert_eq!(entry.year_conflict(), None);
+
+        let mut entry = matched_entry(watch("1917", Some(2017)), Some(matched), &client);
+        let conflict = YearConflict {
+            parsed: 2017,
+            matched: 2019,
+        };
+        assert_eq!(entry.correct_release_year(), Some(conflict));
+        assert_eq!(entry.release_year, Some(2019));
+        assert_eq!(entry.year_conflict(), None);
+    }
+
+    #[test]
+    fn fills_blank_overviews_from_the_fallback_language() {
+        let translations = r#"{"id":438631,"translations":[
+            {"iso_3166_1":"FR","iso_639_1":"fr","data":{"overview":""}},
+            {"iso_3166_1":"US","iso_639_1":"en","data":{"overview":"Paul goes to Arrakis."}}
+        ]}"#;
+        let (base_url, requests) = mock_server(vec![(200, translations)]);
+        let client = TmdbClient::new("key").with_base_url(base_url).with_language("fr-FR");
+        let mut dune = movie(438631, "Dune");
+        dune.overview = Some(String::new());
+        let matched = matched_entry(watch("Dune", None), Some(dune), &client);
+        let mut entries = vec![matched.clone(), matched];
+        let mut cache = MovieCache::default();
+
+        attach_overviews(&mut entries, &client, &mut cache).expect("overviews");
+        for entry in &entries {
+            let overview = entry.movie.as_ref().and_then(|movie| movie.overview.as_deref());
+            assert_eq!(overview, Some("Paul goes to Arrakis."));
+        }
+        assert!(requests.recv().expect("request").contains("/3/movie/438631/translations"));
+        assert!(requests.try_recv().is_err());
+    }
+
+    #[test]
+    fn downloads_each_poster_once_and_skips_failures() {
+        let root = crate::test_support::scratch_dir("posters");
+        let responses = vec![(200, "poster bytes"), (404, "{}"), (200, "larger poster")];
+        let (base_url, requests) = mock_server(responses);
+        let client = TmdbClient::new("key").with_image_base(base_url);
+        let mut dune = movie(438631, "Dune");
+        dune.poster_path = Some("/dune.jpg".to_string());
+        let mut alien = movie(348, "Alien");
+        alien.poster_path = Some("/alien.png".to_string());
+        let mut entries: Vec<EnrichedEntry> = [dune, alien]
+            .into_iter()
+            .map(|movie| matched_entry(watch("x", None), Some(movie), &client))
+            .collect();
+        let alien_url = entries[1].poster_url.clone().expect("alien poster");
+
+        let summary = download_posters(&mut entries, &client, &root).expect("download");
+        assert_eq!(summary.fetched, 1);
+        assert_eq!(summary.cached, 0);
+        assert_eq!(summary.failed.len(), 1);
+        assert_eq!(summary.failed[0].0, alien_url);
+        let saved = root.join("438631-w342.jpg");
+        assert_eq!(entries[0].poster_url, Some(saved.display().to_string()));
+        assert_eq!(std::fs::read_to_string(&saved).expect("poster"), "poster bytes");
+        assert_eq!(entries[1].poster_url, Some(alien_url));
+        assert!(requests.recv().expect("request").contains("/w342/dune.jpg"));
+
+        let mut again = vec![matched_entry(watch("x", None), entries[0].movie.clone(), &client)];
+        let summary = download_posters(&mut again, &client, &root).expect("download again");
+        assert_eq!((summary.fetched, summary.cached), (0, 1));
+
+        // Another size is another file.
+        let mut larger = again.clone();
+        larger[0].poster_url = client.poster_url(larger[0].movie.as_ref().expect("movie"), "w780");
+        let summary = download_posters(&mut larger, &client, &root).expect("download larger");
+        assert_eq!(summary.fetched, 1);
+        let larger_file = root.join("438631-w780.jpg");
+        assert_eq!(std::fs::read_to_string(&larger_file).expect("poster"), "larger poster");
+        assert_eq!(std::fs::read_to_string(&saved).expect("poster"), "poster bytes");
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn poster_size_is_chosen_per_call() {
+        let (base_url, _requests) = mock_server(vec![(
+            200,
+            r#"{"results":[{"id":438631,"title":"Dune","poster_path":"/dune.jpg"}]}"#,
+        )]);
+        let client = TmdbClient::new("key").with_base_url(base_url);
+        let mut cache = MovieCache::default();
+        let detail = EnrichOptions::default().with_poster_size("w780");
+        let entries = vec![watch("Dune", None)];
+        let enriched = enrich_entries(entries, &client, &mut cache, &detail).expect("enrich");
+        let poster = enriched[0].poster_url.as_deref().expect("poster");
+        assert!(poster.ends_with("/w780/dune.jpg"));
+
+        assert_eq!(EnrichOptions::default().with_poster_size(" w154 ").poster_size(), "w154");
+        let unknown = EnrichOptions::default().with_poster_size("w9000");
+        assert_eq!(unknown.poster_size(), DEFAULT_POSTER_SIZE);
+    }
+
+    #[test]
+    fn pinned_matches_outlive_the_ttl() {
+        let (base_url, requests) = mock_server(Vec::new());
+        let client = TmdbClient::new("key").with_base_url(base_url);
+        let options = EnrichOptions::default();
+        let mut cache = MovieCache::default().with_ttl(Duration::from_secs(60));
+        cache.entries.insert(cache_key("Dune", None), Cached::now(Some(movie(841, "Dune"))));
+        cache.pin_match("Dune", None, movie(438631, "Dune"));
+        cache.entries.get_mut("dune").expect("pinned").fetched_at = Some(unix_now() - 120);
+
+        let entries = vec![watch("Dune", None)];
+        let overrides = TitleOverrides::default();
+        let refined =
+            refine_entries(&entries, &client, &mut cache, &overrides, 1.1).expect("refine");
+        assert_eq!(refined, 0);
+        let enriched = enrich_entries(entries, &client, &mut cache, &options).expect("enrich");
+        assert_eq!(enriched[0].movie.as_ref().map(|movie| movie.id), Some(438631));
+        assert!(requests.try_recv().is_err());
+
+        let saved = serde_json::to_string(&cache).expect("serialize");
+        let reloaded: MovieCache = serde_json::from_str(&saved).expect("reload");
+        assert!(reloaded.pinned.contains("dune"));
+    }
+
+    #[test]
+    fn imdb_urls_are_fetched_once_per_matched_film() {
+        let (base_url, requests) =
+            mock_server(vec![(200, r#"{"id":438631,"imdb_id":"tt1160419"}"#)]);
+        let client = TmdbClient::new("key").with_base_url(base_url);
+        let hinted = WatchEntry {
+            raw_title: "Alien {imdb-tt0078748}".to_string(),
+            ..watch("Alien", None)
+        };
+        let mut entries = vec![
+            matched_entry(watch("Dune", None), Some(movie(438631, "Dune")), &client),
+            matched_entry(watch("Dune", None), Some(movie(438631, "Dune")), &client),
+            matched_entry(hinted, Some(movie(348, "Alien")), &client),
+            matched_entry(watch("Home Video", None), None, &client),
+        ];
+        let mut cache = MovieCache::default();
+
+        attach_imdb_ids(&mut entries, &client, &mut cache).expect("imdb ids");
+        let urls: Vec<Option<&str>> =
+            entries.iter().map(|entry| entry.imdb_url.as_deref()).collect();
+        assert_eq!(
+            urls,
+            vec![
+                Some("https://www.imdb.com/title/tt1160419"),
+                Some("https://www.imdb.com/title/tt1160419"),
+                Some("https://www.imdb.com/title/tt0078748"),
+                None,
+            ]
+        );
+        assert!(requests.recv().expect("request").contains("/3/movie/438631/external_ids"));
+        assert!(requests.try_recv().is_err());
+    }
+
+    #[test]
+    fn source_paths_survive_enrichment() {
+        let client = TmdbClient::new("key");
+        let logged = "2025-01-01T20:00:00Z|D:\\Films\\Heat (1995)\\Heat.1995.1080p.mkv";
+        let watch = crate::parse_log_line(logged).expect("entry");
+        let enriched = matched_entry(watch, Some(movie(949, "Heat")), &client);
+        let json = serde_json::to_value(&enriched).expect("serialize");
+        assert_eq!(json["source_path"], "D:\\Films\\Heat (1995)\\Heat.1995.1080p.mkv");
+        assert_eq!(json["raw_title"], "Heat.1995.1080p");
+        assert!(json.get("error").is_none());
+    }
+
+    #[test]
+    fn builds_search_variants() {
+        assert_eq!(search_variants("The Matrix"), vec!["The Matrix", "Matrix"]);
+        assert_eq!(
+            search_variants("Fast & Furious"),
+            vec!["Fast & Furious", "Fast and Furious"]
+        );
+        assert_eq!(search_variants("Dune"), vec!["Dune"]);
+    }
+
+    #[test]
+    fn falls_back_to_the_unaccented_title() {
+        assert_eq!(search_variants("Am\u{e9}lie"), vec!["Am\u{e9}lie", "Amelie"]);
+
+        let (base_url, requests) = mock_server(vec![
+            (200, r#"{"results":[]}"#),
+            (200, r#"{"results":[{"id":194,"title":"Amelie"}]}"#),
+        ]);
+        let client = TmdbClient::new("key").with_base_url(base_url);
+        let options = EnrichOptions::default();
+        let mut cache = MovieCache::default();
+        let entries = vec![watch("Am\u{e9}lie", None)];
+        let enriched = enrich_entries(entries, &client, &mut cache, &options).expect("enrich");
+        assert_eq!(enriched[0].cleaned_title, "Am\u{e9}lie");
+        assert_eq!(enriched[0].movie.as_ref().map(|movie| movie.id), Some(194));
+        assert!(requests.recv().expect("accented").contains("query=Am%C3%A9lie"));
+        assert!(requests.recv().expect("folded").contains("query=Amelie"));
+    }
+
+    #[test]
+    fn expands_numbered_sequels_to_chapters_and_back() {
+        assert_eq!(search_variants("John Wick 4"), vec!["John Wick 4", "John Wick Chapter 4"]);
+        assert_eq!(search_variants("John Wick Chapter 4"), vec!["John Wick Chapter 4", "John Wick 4"]);
+        assert_eq!(search_variants("Blade Runner 2049"), vec!["Blade Runner 2049"]);
+    }
+
+    #[test]
+    fn both_sequel_forms_resolve_to_the_same_film() {
+        const JOHN_WICK_4: &str =
+            r#"{"results":[{"id":603692,"title":"John Wick: Chapter 4","original_title":"John Wick: Chapter 4"}]}"#;
+        let (base_url, requests) = mock_server(vec![
+            (200, JOHN_WICK_4),
+            (200, r#"{"results":[]}"#),
+            (200, JOHN_WICK_4),
+        ]);
+        let client = TmdbClient::new("key").with_base_url(base_url);
+        let options = EnrichOptions::default();
+        let mut cache = MovieCache::default();
+
+        let entries = vec![
+            crate::parse_log_line("John.Wick.Chapter.4.2023.1080p.mkv").expect("entry"),
+            crate::parse_log_line("John.Wick.4.2023.mkv").expect("entry"),
+        ];
+        let enriched = enrich_entries(entries, &client, &mut cache, &options).expect("enrich");
+
+        let ids: Vec<_> = enriched.iter().map(|entry| entry.movie.as_ref().map(|m| m.id)).collect();
+        assert_eq!(ids, vec![Some(603692), Some(603692)]);
+        let queries: Vec<String> = requests.try_iter().collect();
+        assert_eq!(queries.len(), 3);
+        assert!(queries[2].contains("query=John+Wick+Chapter+4"), "{}", queries[2]);
+    }
+
+    #[test]
+    fn parallel_enrichment_keeps_order_and_skips_cached_titles() {
+        const RESULTS: &str = r#"{"results":[
+            {"id":1,"title":"Alien","release_date":"1979-05-25"},
+            {"id":2,"title":"Heat","release_date":"1995-12-15"},
+            {"id":3,"title":"Tenet","release_date":"2020-08-22"}]}"#;
+        let (base_url, requests) =
+            mock_server(vec![(200, RESULTS), (200, RESULTS), (200, RESULTS)]);
+        let client = TmdbClient::new("key").with_base_url(base_url);
+        let mut cache = MovieCache::default();
+        cache
+            .entries
+            .insert(cache_key("Dune", None), Cached::now(Some(movie(438631, "Dune"))));
+
+        let entries = vec![
+            watch("Alien", Some(1979)),
+            watch("Heat", Some(1995)),
+            watch("Dune", None),
+            watch("Tenet", Some(2020)),
+            watch("Heat", Some(1995)),
+        ];
+        let options = EnrichOptions::default();
+        let force_tv = ForceTvList::default();
+        let enriched = enrich_entries_parallel(
+            entries,
+            &client,
+            &mut cache,
+            &force_tv,
+            &options,
+            DEFAULT_CONCURRENCY,
+        )
+        .expect("enrich");
+
+        let ids: Vec<Option<u32>> = enriched
+            .iter()
+            .map(|entry| entry.movie.as_ref().map(|movie| movie.id))
+            .collect();
+        assert_eq!(ids, vec![Some(1), Some(2), Some(438631), Some(3), Some(2)]);
+        assert_eq!(requests.iter().take(3).count(), 3);
+        assert!(requests.try_recv().is_err(), "cached and repeated titles need no request");
+        assert_eq!(cache.entries.len(), 4);
+    }
+
+    #[test]
+    fn parallel_enrichment_routes_like_the_sequential_path() {
+        let source = MockMovieSource::default()
+            .with_movie("Fargo", 275)
+            .with_movie("Heat", 949)
+            .with_show("Fargo", 60622);
+        let force_tv = ForceTvList::from_titles(["fargo"]);
+        let options = EnrichOptions::default();
+        let entries = || vec![watch("Fargo", None), watch("Heat", Some(1995))];
+        let ids = |enriched: Vec<EnrichedEntry>| -> Vec<(Option<u32>, Option<u32>)> {
+            enriched
+                .iter()
+                .map(|entry| {
+                    let movie = entry.movie.as_ref().map(|movie| movie.id);
+                    (movie, entry.show.as_ref().map(|show| show.id))
+                })
+                .collect()
+        };
+
+        let mut cache = MovieCache::default();
+        let parallel =
+            enrich_entries_parallel(entries(), &source, &mut cache, &force_tv, &options, 2)
+                .expect("parallel");
+        let mut cache = MovieCache::default();
+        let routed =
+            enrich_entries_routed(entries(), &source, &mut cache, &force_tv, &options, |_| {})
+                .expect("routed");
+
+        assert_eq!(ids(parallel), [(None, Some(60622)), (Some(949), None)]);
+        assert_eq!(ids(routed), [(None, Some(60622)), (Some(949), None)]);
+        assert!(source.lookups().iter().all(|(title, _)| title == "Heat"));
+    }
+
+    #[test]
+    fn failed_parallel_lookup_leaves_cache_untouched() {
+        let (base_url, _requests) = mock_server(vec![
+            (404, r#"{"status_message":"not found"}"#),
+            (404, r#"{"status_message":"not found"}"#),
+        ]);
+        let client = TmdbClient::new("key").with_base_url(base_url);
+        let mut cache = MovieCache::default();
+
+        let entries = vec![watch("Alien", None), watch("Heat", None)];
+        let options = EnrichOptions::default();
+        let force_tv = ForceTvList::default();
+        let result = enrich_entries_parallel(entries, &client, &mut cache, &force_tv, &options, 2);
+
+        assert!(matches!(result, Err(TmdbError::HttpStatus { code: 404, .. })));
+        assert!(cache.entries.is_empty());
+        assert!(cache.confidence.is_empty());
+    }
+
+    #[test]
+    fn raw_results_are_attached_only_when_asked_for() {
+        const RESULTS: &str =
+            r#"{"results":[{"id":603,"title":"The Matrix","popularity":81.5,"vote_count":26000}]}"#;
+        let (base_url, requests) = mock_server(vec![(200, RESULTS), (200, RESULTS)]);
+        let plain = TmdbClient::new("key").with_base_url(base_url.clone());
+        let raw = TmdbClient::new("key").with_base_url(base_url).with_raw_results(true);
+        let options = EnrichOptions::default();
+        let mut cache = MovieCache::default();
+
+        let enriched = enrich_entries(vec![watch("The Matrix", None)], &plain, &mut cache, &options)
+            .expect("enrich");
+        assert!(enriched[0].movie.as_ref().expect("match").raw.is_none());
+
+        // The cached match has no raw JSON yet, so it is searched again.
+        let enriched = enrich_entries(vec![watch("The Matrix", None)], &raw, &mut cache, &options)
+            .expect("enrich");
+        let attached = enriched[0].movie.as_ref().and_then(|movie| movie.raw.as_ref());
+        assert_eq!(attached.and_then(|raw| raw["popularity"].as_f64()), Some(81.5));
+        assert_eq!(requests.iter().take(2).count(), 2);
+
+        let enriched = enrich_entries(vec![watch("The Matrix", None)], &plain, &mut cache, &options)
+            .expect("enrich");
+        assert!(enriched[0].movie.as_ref().expect("match").raw.is_none());
+    }
+
+    #[test]
+    fn release_year_reaches_the_search_and_picks_the_matching_film() {
+        let (base_url, requests) = mock_server(vec![(
+            200,
+            r#"{"results":[
+                {"id":38575,"title":"The Karate Kid","release_date":"2010-06-10"},
+                {"id":1885,"title":"The Karate Kid","release_date":"1984-06-22"}]}"#,
+        )]);
+        let client = TmdbClient::new("key").with_base_url(base_url);
+        let options = EnrichOptions::default();
+        let mut cache = MovieCache::default();
+
+        let entries = vec![crate::parse_log_line("The.Karate.Kid.1984.720p.mkv").expect("entry")];
+        let enriched = enrich_entries(entries, &client, &mut cache, &options).expect("enrich");
+
+        assert_eq!(enriched[0].movie.as_ref().map(|movie| movie.id), Some(1885));
+        let head = requests.recv().expect("request");
+        assert!(head.contains("year=1984"), "{head}");
+    }
+
+    #[test]
+    fn id_hints_fetch_the_pinned_movie_instead_of_searching() {
+        let (base_url, requests) = mock_server(vec![
+            (200, r#"{"id":1091,"title":"The Thing","release_date":"1982-06-25"}"#),
+            (200, r#"{"movie_results":[{"id":1640,"title":"Crash"}]}"#),
+        ]);
+        let client = TmdbClient::new("key").with_base_url(base_url);
+        let options = EnrichOptions::default();
+        let mut cache = MovieCache::default();
+
+        let entries = vec![
+            crate::parse_log_line("The.Thing.{tmdb-1091}.mkv").expect("entry"),
+            crate::parse_log_line("Crash.2004.{imdb-tt0375679}.mkv").expect("entry"),
+            crate::parse_log_line("The.Thing.{tmdb-1091}.mkv").expect("entry"),
+        ];
+        let enriched = enrich_entries(entries, &client, &mut cache, &options).expect("enrich");
+
+        let ids: Vec<Option<u32>> = enriched
+            .iter()
+            .map(|entry| entry.movie.as_ref().map(|movie| movie.id))
+            .collect();
+        assert_eq!(ids, vec![Some(1091), Some(1640), Some(1091)]);
+        assert!(requests.recv().expect("request").starts_with("GET /3/movie/1091?"));
+        assert!(requests.recv().expect("request").starts_with("GET /3/find/tt0375679?"));
+        assert!(requests.try_recv().is_err(), "the repeat should hit the cache");
+        assert_eq!(cache.confidence.get("tmdb:1091"), Some(&1.0));
+        assert!(!cache.entries.contains_key(&cache_key("The Thing", None)));
+    }
+
+    #[test]
+    fn overrides_outrank_the_cache_and_search() {
+        let (base_url, requests) = mock_server(vec![(200, r#"{"id":194,"title":"Amélie"}"#)]);
+        let client = TmdbClient::new("key").with_base_url(base_url);
+        let overrides = TitleOverrides::from_ids([("AMELIE.fr.DVDRip", 194)]);
+        let options = EnrichOptions::default().with_overrides(overrides);
+        let mut cache = MovieCache::default();
+        cache.entries.insert(cache_key("Amelie fr", None), Cached::now(Some(movie(7, "Amelia"))));
+
+        let amelie = || WatchEntry {
+            raw_title: "Amelie.fr.DVDRip".to_string(),
+            ..watch("Amelie fr", None)
+        };
+        let force_tv = ForceTvList::default();
+        for _ in 0..2 {
+            let enriched = enrich_entries_parallel(
+                vec![amelie()],
+                &client,
+                &mut cache,
+                &force_tv,
+                &options,
+                2,
+            )
+            .expect("parallel");
+            assert_eq!(enriched[0].movie.as_ref().map(|movie| movie.id), Some(194));
+            let enriched = enrich_entries(vec![amelie()], &client, &mut cache, &options)
+                .expect("enrich");
+            assert_eq!(enriched[0].movie.as_ref().map(|movie| movie.id), Some(194));
+        }
+        assert!(requests.recv().expect("by id").starts_with("GET /3/movie/194?"));
+        assert!(requests.try_recv().is_err(), "the override's movie is cached by id");
+
+        let root = crate::test_support::scratch_dir("title_overrides");
+        assert!(TitleOverrides::load(&root.join("missing.json")).expect("missing").is_empty());
+        let path = root.join("overrides.json");
+        std::fs::write(&path, r#"{"Amelie fr": 194}"#).expect("write overrides");
+        let loaded = TitleOverrides::load(&path).expect("load");
+        assert_eq!(loaded.id_for(&watch("amelie FR", None)), Some(194));
+        std::fs::write(&path, "[194]").expect("write overrides");
+        let err = TitleOverrides::load(&path).expect_err("not an object");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        let (skipped, warning) = TitleOverrides::load_checked(&path);
+        assert!(skipped.is_empty());
+        assert!(warning.is_some_and(|warning| warning.contains("overrides.json")));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn routes_episodes_to_tv_search() {
+        let (base_url, requests) = mock_server(vec![(
+            200,
+            r#"{"results":[{"id":2316,"name":"The Office","poster_path":"/office.jpg"}]}"#,
+        )]);
+        let client = TmdbClient::new("key").with_base_url(base_url);
+        let options = EnrichOptions::default();
+        let mut cache = MovieCache::default();
+
+        let entries = vec![
+            crate::parse_log_line("The.Office.S03E05.720p.mkv").expect("entry"),
+            crate::parse_log_line("The.Office.S03E06.720p.mkv").expect("entry"),
+        ];
+        let enriched = enrich_entries(entries, &client, &mut cache, &options).expect("enrich");
+
+        assert!(enriched.iter().all(|entry| entry.movie.is_none()));
+        assert_eq!(enriched[1].show.as_ref().map(|show| show.id), Some(2316));
+        assert_eq!(enriched[1].tmdb_url.as_deref(), Some("https://www.themoviedb.org/tv/2316"));
+        let head = requests.recv().expect("request");
+        assert!(head.starts_with("GET /3/search/tv?"), "{head}");
+        assert!(head.contains("query=The+Office"), "{head}");
+        assert!(requests.try_recv().is_err(), "second episode should hit the cache");
+    }
+
+    #[test]
+    fn forced_tv_titles_skip_movie_search() {
+        let (base_url, requests) = mock_server(vec![(
+            200,
+            r#"{"results":[{"id":60622,"name":"Fargo","first_air_date":"2014-04-15"}]}"#,
+        )]);
+        let client = TmdbClient::new("key").with_base_url(base_url);
+        let options = EnrichOptions::default();
+        let mut cache = MovieCache::default();
+        let force_tv = ForceTvList::from_titles(["fargo"]);
+
+        let entries = vec![watch("Fargo", None)];
+        let enriched =
+            enrich_entries_routed(entries, &client, &mut cache, &force_tv, &options, |_| {})
+                .expect("enrich");
+
+        assert_eq!(enriched[0].show.as_ref().map(|show| show.id), Some(60622));
+        assert!(enriched[0].movie.is_none());
+        let head = requests.recv().expect("request");
+        assert!(head.starts_with("GET /3/search/tv?"), "{head}");
+    }
+
+    #[test]
+    fn counts_rewatches_most_recent_first() {
+        let client = TmdbClient::new("key");
+        let watched = |title: &str, year, at: Option<&str>, id: Option<u32>| {
+            let mut entry = watch(title, year);
+            entry.watched_at = at.map(str::to_string);
+            matched_entry(entry, id.map(|id| movie(id, title)), &client)
+        };
+        let entries = vec![
+            watched("Dune", Some(2021), Some("2024-03-01T20:00:00Z"), Some(438631)),
+            watched("Heat", Some(1995), Some("2024-05-01T20:00:00Z"), Some(949)),
+            watched("Home Video", None, None, None),
+            watched("dune", Some(2021), Some("2025-01-10T20:00:00+02:00"), None),
+            watched("Dune", Some(1984), Some("2023-01-01T20:00:00Z"), Some(841)),
+            watched("Dune", Some(2021), Some("2022-11-01T20:00:00Z"), Some(438631)),
+        ];
+
+        let aggregated = aggregate_watches(&entries);
+        let summary: Vec<(&str, Option<i32>, usize)> = aggregated
+            .iter()
+            .map(|entry| (entry.cleaned_title.as_str(), entry.release_year, entry.watch_count))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Dune", Some(2021), 3),
+                ("Heat", Some(1995), 1),
+                ("Dune", Some(1984), 1),
+                ("Home Video", None, 1),
+            ]
+        );
+        let dune = &aggregated[0];
+        assert_eq!(dune.movie.as_ref().map(|movie| movie.id), Some(438631));
+        assert_eq!(dune.first_watched.as_deref(), Some("2022-11-01T20:00:00Z"));
+        assert_eq!(dune.last_watched.as_deref(), Some("2025-01-10T20:00:00+02:00"));
+        assert!(aggregated[3].last_watched.is_none());
+    }
+
+    #[test]
+    fn groups_matched_films_by_collection() {
+        let matrix = r#"{"id":603,"runtime":136,
+            "belongs_to_collection":{"id":2344,"name":"The Matrix Collection"}}"#;
+        let reloaded = r#"{"id":604,"runtime":138,
+            "belongs_to_collection":{"id":2344,"name":"The Matrix Collection"}}"#;
+        let heat = r#"{"id":949,"runtime":170,"belongs_to_collection":null}"#;
+        let (base_url, requests) = mock_server(vec![(200, matrix), (200, reloaded), (200, heat)]);
+        let client = TmdbClient::new("key").with_base_url(base_url);
+        let mut cache = MovieCache::default();
+
+        let mut entries = vec![
+            matched_entry(watch("The Matrix", Some(1999)), Some(movie(603, "The Matrix")), &client),
+            matched_entry(watch("Reloaded", Some(2003)), Some(movie(604, "Reloaded")), &client),
+            matched_entry(watch("The Matrix", Some(1999)), Some(movie(603, "The Matrix")), &client),
+            matched_entry(watch("Heat", Some(1995)), Some(movie(949, "Heat")), &client),
+            matched_entry(watch("Home Video", None), None, &client),
+        ];
+        attach_details(&mut entries, &client, &mut cache).expect("details");
+        assert_eq!(requests.iter().take(3).count(), 3);
+
+        assert_eq!(
+            group_by_collection(&entries),
+            vec![
+                CollectionGroup {
+                    id: Some(2344),
+                    name: "The Matrix Collection".to_string(),
+                    films: 2,
+                    watches: 3,
+                },
+                CollectionGroup {
+                    id: None,
+                    name: UNGROUPED_COLLECTION.to_string(),
+                    films: 1,
+                    watches: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn enriches_through_any_movie_source() {
+        let source = MockMovieSource::default().with_movie("Dune", 438631);
+        let mut cache = MovieCache::default();
+        let entries = vec![watch("Dune", Some(2021)), watch("Unknown Film", None)];
+        let enriched = enrich_entries(entries, &source, &mut cache, &EnrichOptions::default())
+            .expect("enrich");
+
+        assert_eq!(enriched[0].movie.as_ref().map(|movie| movie.id), Some(438631));
+        assert!(enriched[0].poster_url.is_none());
+        assert!(enriched[1].movie.is_none());
+    }
+
+    #[test]
+    fn cache_hits_skip_lookups() {
+        let source = MockMovieSource::default().with_movie("Dune", 438631);
+        let mut cache = MovieCache::default();
+        let options = EnrichOptions::default();
+
+        let twice = vec![watch("Dune", Some(2021)), watch("dune", Some(2021))];
+        enrich_entries(twice, &source, &mut cache, &options).expect("enrich");
+        let again = enrich_entries(vec![watch("Dune", Some(2021))], &source, &mut cache, &options)
+            .expect("enrich again");
+
+        assert_eq!(source.lookups(), vec![("Dune".to_string(), Some(2021))]);
+        assert_eq!(again[0].movie.as_ref().map(|movie| movie.id), Some(438631));
+    }
+
+    #[test]
+    fn misses_are_cached() {
+        let source = MockMovieSource::default();
+        let mut cache = MovieCache::default();
+        let options = EnrichOptions::default();
+
+        enrich_entries(vec![watch("Unknown Film", None)], &source, &mut cache, &options)
+            .expect("enrich");
+        let searched = source.lookups().len();
+        let again = enrich_entries(vec![watch("Unknown Film", None)], &source, &mut cache, &options)
+            .expect("enrich again");
+
+        assert!(searched > 0);
+        assert_eq!(source.lookups().len(), searched, "a cached miss was searched again");
+        assert!(again[0].movie.is_none());
+        assert!(cache.entries[&cache_key("Unknown Film", None)].value.is_none());
+    }
+
+    #[test]
+    fn empty_titles_are_never_looked_up() {
+        let source = MockMovieSource::default().with_movie("", 1);
+        let mut cache = MovieCache::default();
+        let entries = vec![watch("", None), watch("   ", Some(2021))];
+        let enriched = enrich_entries(entries, &source, &mut cache, &EnrichOptions::default())
+            .expect("enrich");
+
+        assert!(source.lookups().is_empty());
+        assert!(enriched.iter().all(|entry| entry.movie.is_none()));
+        assert!(cache.entries.is_empty());
+    }
+
+    #[test]
+    fn cache_keys_include_the_year() {
+        assert_eq!(cache_key(" Dune ", Some(2021)), "dune|2021");
+        assert_eq!(cache_key("Dune", None), "dune");
+        assert_ne!(cache_key("Dune", Some(2021)), cache_key("Dune", Some(1984)));
+
+        let source = MockMovieSource::default().with_movie("Dune", 438631);
+        let mut cache = MovieCache::default();
+        let remakes = vec![watch("Dune", Some(2021)), watch("Dune", Some(1984))];
+        enrich_entries(remakes, &source, &mut cache, &EnrichOptions::default()).expect("enrich");
+
+        let years: Vec<Option<i32>> = source.lookups().into_iter().map(|(_, year)| year).collect();
+        assert_eq!(years, vec![Some(2021), Some(1984)]);
+    }
+
+    #[test]
+    fn lenient_enrichment_keeps_going_past_failed_lookups() {
+        let source = MockMovieSource::default()
+            .with_movie("Dune", 438631)
+            .with_failure("Alien")
+            .with_movie("Heat", 949);
+        let mut cache = MovieCache::default();
+        let entries = vec![watch("Dune", None), watch("Alien", None), watch("Heat", None)];
+        let options = EnrichOptions::default();
+
+        let (enriched, failures) = enrich_entries_lenient(entries, &source, &mut cache, &options);
+        let ids: Vec<Option<u32>> =
+            enriched.iter().map(|entry| entry.movie.as_ref().map(|movie| movie.id)).collect();
+        assert_eq!(ids, vec![Some(438631), None, Some(949)]);
+        assert_eq!(enriched[0].error, None);
+        assert!(enriched[1].error.as_deref().is_some_and(|error| error.contains("500")));
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, "Alien");
+        assert!(matches!(failures[0].1, TmdbError::HttpStatus { code: 500, .. }));
+
+        assert!(cache.entries.contains_key("dune"));
+        assert!(cache.entries.contains_key("heat"));
+        assert!(!cache.entries.contains_key("alien"));
+        assert!(!cache.misses.contains_key("alien"));
+
+        let strict = enrich_entries(vec![watch("Alien", None)], &source, &mut cache, &options);
+        assert!(strict.is_err());
+    }
+
+    #[test]
+    fn trailing_articles_are_searched_up_front() {
+        assert_eq!(lookup_title("Matrix, The"), "The Matrix");
+        assert_eq!(lookup_title("Beautiful Mind, a"), "A Beautiful Mind");
+        assert_eq!(lookup_title("Airplane!"), "Airplane");
+        let listed = "Crouching Tiger, Hidden Dragon";
+        assert_eq!(lookup_title(listed), listed);
+
+        let entry = crate::parse_log_line("2025-01-01T20:00:00Z|/movies/Matrix, The.1999.mkv")
+            .expect("entry");
+        let source = MockMovieSource::default().with_movie("The Matrix", 603);
+        let mut cache = MovieCache::default();
+        let enriched = enrich_entries(vec![entry], &source, &mut cache, &EnrichOptions::default())
+            .expect("enrich");
+
+        assert_eq!(source.lookups(), vec![("The Matrix".to_string(), Some(1999))]);
+        assert_eq!(enriched[0].cleaned_title, "Matrix, The");
+        assert_eq!(enriched[0].movie.as_ref().map(|movie| movie.id), Some(603));
+        assert!(cache.entries.contains_key("the matrix|1999"));
+    }
+
+    #[test]
+    fn shaky_matches_are_kept_but_flagged() {
+        let client = TmdbClient::new("key").with_base_url("http://127.0.0.1:9");
+        let options = EnrichOptions::default().with_confidence_threshold(0.5);
+        let mut cache = MovieCache::default();
+        let dune = cache_key("Dune", Some(2021));
+        cache.entries.insert(dune.clone(), Cached::now(Some(movie(438631, "Dune"))));
+        let alien = cache_key("Alein", Some(1979));
+        cache.entries.insert(alien.clone(), Cached::now(Some(movie(1, "Aliens in the Attic"))));
+
+        let entries = vec![watch("Dune", Some(2021)), watch("Alein", Some(1979))];
+        let force_tv = ForceTvList::default();
+        let enriched =
+            enrich_entries_routed(entries, &client, &mut cache, &force_tv, &options, |_| {})
+                .expect("enrich");
+
+        assert_eq!(enriched.len(), 2);
+        assert_eq!(enriched[0].confidence, Some(1.0));
+        assert!(!enriched[0].low_confidence);
+        assert!(enriched[1].confidence.is_some_and(|score| score < 0.5));
+        assert!(enriched[1].low_confidence);
+        assert_eq!(cache.confidence.get(&dune), Some(&1.0));
+        assert_eq!(cache.confidence.get(&alien).copied(), enriched[1].confidence);
+    }
+
+    #[test]
+    fn entry_ages_fall_back_to_the_file_time() {
+        let root = crate::test_support::scratch_dir("cache-ages");
+        let path = root.join("cache.json");
+        let stamped = unix_now() - 3_600;
+        let stored = format!(
+            concat!(
+                r#"{{"version":{},"entries":{{"#,
+                r#""dune|2021":{{"fetched_at":{},"value":null}},"heat|1995":null}},"#,
+                r#""shows":{{"the office":{{"fetched_at":{},"value":null}}}}}}"#
+            ),
+            CACHE_VERSION,
+            stamped,
+            unix_now()
+        );
+        std::fs::write(&path, stored).expect("write cache");
+
+        let cache = MovieCache::load(&path);
+        let age = |key: &str| cache.entry_age(key).map(|age| age.as_secs());
+        assert!(age("dune|2021").is_some_and(|secs| (3_600..3_700).contains(&secs)));
+        assert!(age("heat|1995").is_some_and(|secs| secs < 100), "dated by the file");
+        assert_eq!(age("alien"), None);
+
+        let info = cache.info();
+        assert_eq!((info.movies, info.shows, info.undated), (2, 1, 0));
+        assert_eq!(info.oldest.map(|(key, _)| key).as_deref(), Some("dune|2021"));
+
+        let mut fresh = MovieCache::default();
+        fresh.entries.insert(
+            "heat|1995".to_string(),
+            Cached {
+                value: None,
+                fetched_at: None,
+            },
+        );
+        assert_eq!(fresh.entry_age("heat|1995"), None);
+        assert_eq!(fresh.info().undated, 1);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn spelling_variants_share_a_cache_key() {
+        assert_eq!(cache_key("Amélie", Some(2001)), cache_key("Amelie", Some(2001)));
+        assert_eq!(cache_key("The  Matrix", None), cache_key(" the matrix ", None));
+        assert_eq!(cache_key("Amélie", Some(2001)), "amelie|2001");
+        assert_eq!(cache_key("Matrix, The", Some(1999)), cache_key("The Matrix!", Some(1999)));
+
+        let root = crate::test_support::scratch_dir("cache-v3");
+        let path = root.join("cache.json");
+        let stored = concat!(
+            r#"{"version":3,"entries":{"#,
+            r#""amélie|2001":{"fetched_at":1,"value":{"id":194,"title":"Amélie"}},"#,
+            r#""amelie|2001":{"fetched_at":2,"value":{"id":1,"title":"Amelie"}},"#,
+            r#""the  matrix":{"fetched_at":3,"value":null},"#,
+            r#""matrix, the|1999":{"fetched_at":4,"value":{"id":603,"title":"The Matrix"}},"#,
+            r#""the matrix|1999":{"fetched_at":5,"value":{"id":2,"title":"Matrix"}}},"#,
+            r#""pinned":["amélie|2001","matrix, the|1999"]}"#
+        );
+        std::fs::write(&path, stored).expect("write cache");
+
+        // Pinned spellings beat the search results already stored in the new form.
+        let cache = MovieCache::load(&path);
+        assert_eq!(cache.version, CACHE_VERSION);
+        assert_eq!(cache.entries.len(), 3);
+        assert_eq!(cache.entries["amelie|2001"].value.as_ref().map(|movie| movie.id), Some(194));
+        let matrix = cache.entries["the matrix|1999"].value.as_ref();
+        assert_eq!(matrix.map(|movie| movie.id), Some(603));
+        assert!(cache.entries.contains_key("the matrix"));
+        assert!(cache.pinned.contains("amelie|2001"));
+        assert!(cache.pinned.contains("the matrix|1999"));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn migrates_a_bare_map_cache() {
+        let root = crate::test_support::scratch_dir("cache-v0");
+        let path = root.join("cache.json");
+        std::fs::write(&path, r#"{"dune|2021":{"id":438631,"title":"Dune"},"nothing":null}"#)
+            .expect("write cache");
+
+        let (cache, warning) = MovieCache::load_checked(&path);
+        assert!(warning.is_none());
+        assert_eq!(cache.version, CACHE_VERSION);
+        assert_eq!(cache.entries["dune|2021"].value.as_ref().map(|movie| movie.id), Some(438631));
+        assert!(cache.entries["nothing"].value.is_none());
+
+        cache.save(&path).expect("save");
+        let saved: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).expect("read")).expect("json");
+        assert_eq!(saved["version"], CACHE_VERSION);
+        assert!(saved["entries"]["dune|2021"].is_object());
+    }
+
+    #[test]
+    fn drops_details_cached_without_credits() {
+        let root = crate::test_support::scratch_dir("cache-v2");
+        let path = root.join("cache.json");
+        std::fs::write(
+            &path,
+            r#"{"version":2,"entries":{"dune|2021":{"id":438631,"title":"Dune"}},
+                "details":{"438631":{"id":438631,"runtime":155}}}"#,
+        )
+        .expect("write cache");
+
+        let (cache, status) = MovieCache::load_with_status(&path);
+        assert!(status.is_ok());
+        assert!(cache.entries.contains_key("dune|2021"));
+        assert!(cache.details.is_empty());
+    }
+
+    #[test]
+    fn keeps_caches_from_newer_versions() {
+        let root = crate::test_support::scratch_dir("cache-newer");
+        let path = root.join("cache.json");
+        let newer =
+            r#"{"version":99,"entries":{"dune|2021":{"id":438631,"title":"Dune"}},"future":{}}"#;
+        std::fs::write(&path, newer).expect("write cache");
+
+        let (cache, status) = MovieCache::load_with_status(&path);
+        assert_eq!(
+            status,
+            CacheStatus::NewerVersion {
+                path: path.clone(),
+                version: 99
+            }
+        );
+        assert!(status.to_string().contains("newer"));
+        assert!(cache.entries.contains_key("dune|2021"));
+        assert!(cache.save(&path).is_err());
+        assert_eq!(std::fs::read_to_string(&path).expect("read"), newer);
+    }
+
+    #[test]
+    fn reports_an_unreadable_cache_as_structured_status() {
+        let root = crate::test_support::scratch_dir("cache-corrupt");
+        let path = root.join("cache.json");
+        std::fs::write(&path, "not json").expect("write cache");
+
+        let (cache, status) = MovieCache::load_with_status(&path);
+        assert!(cache.entries.is_empty());
+        assert_eq!(status, CacheStatus::LoadCorrupted { path: path.clone() });
+        assert!(status.warning().expect("warning").contains("goo cache repair"));
+        let json = serde_json::to_value(&status).expect("serialize");
+        assert_eq!(json["kind"], "load_corrupted");
+
+        let missing = root.join("missing.json");
+        assert_eq!(MovieCache::load_with_status(&missing).1, CacheStatus::Ok);
+        assert_eq!(CacheStatus::Ok.warning(), None);
+    }
+
+    #[test]
+    fn repairs_a_partially_corrupt_cache() {
+        let root = crate::test_support::scratch_dir("cache-repair");
+        let path = root.join("cache.json");
+        std::fs::write(
+            &path,
+            r#"{"entries":{"dune|2021":{"id":438631,"title":"Dune"},"bad":{"id":"oops"},"missing":null,"alien|1979":{"id":348,"ti"#,
+        )
+        .expect("write cache");
+
+        let (cache, repair) = MovieCache::load_lenient(&path).expect("load");
+        assert_eq!(repair, CacheRepair { recovered: 2, dropped: 2 });
+        assert_eq!(cache.entries["dune|2021"].value.as_ref().map(|movie| movie.id), Some(438631));
+        assert!(cache.entries["missing"].value.is_none());
+        assert!(!cache.entries.contains_key("alien|1979"));
+    }
+
+    #[test]
+    fn repair_migrates_older_cache_formats() {
+        let root = crate::test_support::scratch_dir("cache-repair-old");
+        let path = root.join("cache.json");
+        std::fs::write(&path, r#"{"dune|2021":{"id":438631,"title":"Dune"},"bad":{"id":"x"},"al"#)
+            .expect("write cache");
+        let (cache, repair) = MovieCache::load_lenient(&path).expect("load");
+        assert_eq!(repair, CacheRepair { recovered: 1, dropped: 1 });
+        assert_eq!(cache.version, CACHE_VERSION);
+        assert!(cache.entries.contains_key("dune|2021"));
+
+        let stored = concat!(
+            r#"{"version":4,"entries":{"#,
+            r#""amélie|2001":{"fetched_at":1,"value":{"id":194,"title":"Amélie"}}},"#,
+            r#""pinned":["amélie|2001"],"details":{"194":{"id":"broken"}}"#
+        );
+        std::fs::write(&path, stored).expect("write cache");
+        let (cache, repair) = MovieCache::load_lenient(&path).expect("load");
+        assert_eq!(repair, CacheRepair { recovered: 2, dropped: 1 });
+        assert_eq!(cache.version, CACHE_VERSION);
+        assert!(cache.entries.contains_key("amelie|2001"));
+        assert!(cache.pinned.contains("amelie|2001"));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn expired_and_unstamped_entries_are_looked_up_again() {
+        const DUNE: &str = r#"{"results":[{"id":438631,"title":"Dune"}]}"#;
+        let (base_url, requests) = mock_server(vec![(200, DUNE), (200, DUNE)]);
+        let client = TmdbClient::new("key").with_base_url(base_url);
+
+        let options = EnrichOptions::default();
+        // Written before timestamps existed: a bare miss.
+        let mut cache: MovieCache =
+            serde_json::from_str(r#"{"entries":{"dune|2021":null}}"#).expect("old cache");
+        let dune = || vec![watch("Dune", Some(2021))];
+        let enriched = enrich_entries(dune(), &client, &mut cache, &options).expect("enrich");
+        assert_eq!(enriched[0].movie.as_ref().map(|movie| movie.id), Some(438631));
+        assert!(requests.recv().is_ok());
+
+        // Fresh now, so no request...
+        enrich_entries(dune(), &client, &mut cache, &options).expect("enrich");
+        assert!(requests.try_recv().is_err());
+
+        // ...until it ages past the TTL.
+        let mut cache = cache.with_ttl(Duration::from_secs(60));
+        cache.entries.get_mut("dune|2021").expect("cached").fetched_at = Some(unix_now() - 120);
+        enrich_entries(dune(), &client, &mut cache, &options).expect("enrich");
+        assert!(requests.recv().is_ok());
+    }
+
+    #[test]
+    fn repeated_misses_back_off_until_cleared() {
+        let empty = r#"{"results":[]}"#;
+        let (base_url, requests) = mock_server(vec![(200, empty), (200, empty), (200, empty)]);
+        let client = TmdbClient::new("key").with_base_url(base_url);
+        let options = EnrichOptions::default();
+        let mut cache = MovieCache::default().with_miss_ttl(Duration::from_secs(60));
+        let lookup = |cache: &mut MovieCache| {
+            let entries = vec![watch("Nowhere Film", None)];
+            enrich_entries(entries, &client, cache, &options).expect("enrich");
+        };
+        let age = |cache: &mut MovieCache, seconds: u64| {
+            let cached = cache.entries.get_mut("nowhere film").expect("cached miss");
+            cached.fetched_at = Some(unix_now() - seconds);
+        };
+
+        lookup(&mut cache);
+        assert!(requests.recv().is_ok());
+        age(&mut cache, 90);
+        lookup(&mut cache);
+        assert!(requests.recv().is_ok());
+        assert_eq!(cache.misses.get("nowhere film"), Some(&2));
+
+        // A second miss is trusted twice as long.
+        age(&mut cache, 90);
+        lookup(&mut cache);
+        assert!(requests.try_recv().is_err());
+
+        cache.pin_match("Dune", None, movie(438631, "Dune"));
+        assert_eq!(cache.clear_misses(), 1);
+        assert!(cache.misses.is_empty());
+        assert!(cache.entries.contains_key("dune"));
+        lookup(&mut cache);
+        assert!(requests.recv().is_ok());
+    }
+
+    #[test]
+    fn refine_only_refetches_low_confidence_matches() {
+        let (base_url, requests) = mock_server(vec![(
+            200,
+            r#"{"results":[{"id":348,"title":"Alien","original_title":"Alien"}]}"#,
+        )]);
+        let client = TmdbClient::new("key").with_base_url(base_url);
+
+        let mut cache = MovieCache::default();
+        let dune = cache_key("Dune", Some(2021));
+        cache.entries.insert(dune.clone(), Cached::now(Some(movie(438631, "Dune"))));
+        cache.confidence.insert(dune.clone(), 1.0);
+        let alien = cache_key("Alein", Some(1979));
+        cache.entries.insert(alien.clone(), Cached::now(Some(movie(1, "Aliens in the Attic"))));
+        cache.confidence.insert(alien.clone(), 0.3);
+
+        let entries = vec![watch("Dune", Some(2021)), watch("Alein", Some(1979))];
+        let overrides = TitleOverrides::default();
+        let improved =
+            refine_entries(&entries, &client, &mut cache, &overrides, DEFAULT_REFINE_THRESHOLD)
+                .expect("refine");
+
+        assert_eq!(improved, 1);
+        let head = requests.recv().expect("request");
+        assert!(head.contains("query=Alein"), "{head}");
+        assert!(requests.try_recv().is_err(), "confident match was re-fetched");
+        assert_eq!(cache.entries[&alien].value.as_ref().map(|movie| movie.id), Some(348));
+        assert_eq!(cache.entries[&dune].value.as_ref().map(|movie| movie.id), Some(438631));
+        assert!(cache.confidence[&alien] > 0.3);
+    }
+
+    #[test]
+    fn refine_skips_id_matches_and_prefers_the_release_year() {
+        let source = MockMovieSource::default().with_movie("Amelie", 7);
+        let overrides = TitleOverrides::from_ids([("Amelie", 194)]);
+        let mut cache = MovieCache::default();
+        let hinted = WatchEntry {
+            id_hint: Some(IdHint::Tmdb(603)),
+            ..watch("The Matrix", None)
+        };
+        for title in ["Amelie", "The Matrix"] {
+            cache.entries.insert(cache_key(title, None), Cached::now(Some(movie(1, "Other"))));
+            cache.confidence.insert(cache_key(title, None), 0.1);
+        }
+        let entries = vec![watch("Amelie", None), hinted];
+        let improved = refine_entries(&entries, &source, &mut cache, &overrides, 0.8)
+            .expect("refine");
+        assert_eq!(improved, 0);
+        assert!(source.lookups().is_empty(), "id matches are never re-searched");
+
+        const MUMMIES: &str =
+            r#"{"results":[{"id":335984,"title":"The Mummy","release_date":"2017-06-06"},
+                           {"id":564,"title":"The Mummy","release_date":"1999-05-06"}]}"#;
+        let (base_url, _requests) = mock_server(vec![(200, MUMMIES), (200, MUMMIES)]);
+        let client = TmdbClient::new("key").with_base_url(base_url);
+        let mummy = cache_key("The Mummy", Some(1999));
+        cache.entries.insert(mummy.clone(), Cached::now(Some(movie(1, "Mummies"))));
+        cache.confidence.insert(mummy.clone(), 0.3);
+        let entries = vec![watch("The Mummy", Some(1999))];
+        let improved =
+            refine_entries(&entries, &client, &mut cache, &overrides, 0.8).expect("refine");
+        assert_eq!(improved, 1);
+        assert_eq!(cache.entries[&mummy].value.as_ref().map(|movie| movie.id), Some(564));
+    }
+
+    #[test]
+    fn refine_scores_on_the_scale_enrichment_stores() {
+        const MUMMY: &str =
+            r#"{"results":[{"id":564,"title":"The Mummy","release_date":"1999-05-06"}]}"#;
+        let (base_url, _requests) = mock_server(vec![(200, MUMMY), (200, MUMMY)]);
+        let client = TmdbClient::new("key").with_base_url(base_url);
+        let mut cache = MovieCache::default();
+        let key = cache_key("The Mumy", Some(1999));
+        let cached = TmdbMovie {
+            release_date: Some("1999-05-06".to_string()),
+            ..movie(564, "The Mummy")
+        };
+        let stored = cached.match_confidence("The Mumy");
+        cache.entries.insert(key.clone(), Cached::now(Some(cached)));
+        cache.confidence.insert(key.clone(), stored);
+
+        let entries = vec![watch("The Mumy", Some(1999))];
+        let overrides = TitleOverrides::default();
+        let improved =
+            refine_entries(&entries, &client, &mut cache, &overrides, 0.99).expect("refine");
+        assert_eq!(improved, 0, "the year bonus counted as an improvement");
+        assert_eq!(cache.confidence[&key], stored);
+    }
 }
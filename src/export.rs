@@ -0,0 +1,447 @@
+use crate::app::month_of;
+use crate::enrich::{cache_key, EnrichedEntry};
+use crate::{parse_timestamp, WatchEntry};
+use std::collections::{BTreeMap, HashSet};
+
+/// Renders every timestamped entry as a VEVENT in an iCalendar document.
+/// Entries without a parseable `watched_at` are skipped.
+pub fn to_ics(entries: &[EnrichedEntry]) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//goo//watch history//EN".to_string(),
+    ];
+
+    for (idx, entry) in entries.iter().enumerate() {
+        let Some(watched) = entry.watched_at.as_deref().and_then(parse_timestamp) else {
+            continue;
+        };
+        let start = format!(
+            "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+            watched.year, watched.month, watched.day, watched.hour, watched.minute, watched.second
+        );
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}-{idx}@goo", watched.to_unix()));
+        lines.push(format!("DTSTAMP:{start}"));
+        lines.push(format!("DTSTART:{start}"));
+        if let Some(runtime) = entry.details.as_ref().and_then(|details| details.runtime) {
+            lines.push(format!("DURATION:PT{runtime}M"));
+        }
+        lines.push(format!("SUMMARY:{}", escape_ics(&display_title(entry))));
+        if let Some(url) = entry.tmdb_url.as_deref() {
+            lines.push(format!("URL:{}", escape_ics(url)));
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut output = String::new();
+    for line in lines {
+        output.push_str(&fold_ics_line(&line));
+        output.push_str("\r\n");
+    }
+    output
+}
+
+/// Renders the films in `entries` as a Letterboxd import CSV
+/// (`Title,Year,WatchedDate,tmdbID`). Unmatched films are kept with a blank
+/// id; episodes, shows and streams are left out since Letterboxd only logs films.
+pub fn to_letterboxd_csv(entries: &[EnrichedEntry]) -> String {
+    let mut csv = String::from("Title,Year,WatchedDate,tmdbID\n");
+    for entry in entries {
+        if entry.is_stream || entry.episode.is_some() || entry.show.is_some() {
+            continue;
+        }
+        let year = entry
+            .release_year
+            .or_else(|| entry.movie.as_ref().and_then(|movie| movie.release_year()));
+        // Letterboxd ignores the time, and the local date is the day it was watched.
+        let watched_date = entry
+            .watched_at
+            .as_deref()
+            .filter(|watched| parse_timestamp(watched).is_some())
+            .and_then(|watched| watched.get(..10));
+        let fields = [
+            escape_csv(&entry.cleaned_title),
+            year.map(|year| year.to_string()).unwrap_or_default(),
+            watched_date.unwrap_or_default().to_string(),
+            entry.movie.as_ref().map(|movie| movie.id.to_string()).unwrap_or_default(),
+        ];
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Renders a viewing journal: one Markdown table of date, title, year and
+/// TMDB link per month watched, oldest first, with undated watches last.
+/// Entries TMDB didn't match are listed with a "(no match)" note.
+pub fn to_markdown(entries: &[EnrichedEntry]) -> String {
+    let mut months: BTreeMap<String, Vec<&EnrichedEntry>> = BTreeMap::new();
+    for entry in entries {
+        months.entry(month_of(entry.watched_at.as_deref())).or_default().push(entry);
+    }
+
+    let mut markdown = String::from("# Watch history\n");
+    for (month, watched) in months {
+        markdown.push_str(&format!("\n## {month}\n\n"));
+        markdown.push_str("| Watched | Title | Year | TMDB |\n| --- | --- | --- | --- |\n");
+        for entry in watched {
+            let date = entry
+                .watched_at
+                .as_deref()
+                .filter(|watched| parse_timestamp(watched).is_some())
+                .and_then(|watched| watched.get(..10))
+                .unwrap_or_default();
+            let title = match (&entry.episode, &entry.movie, &entry.show) {
+                (Some(episode), _, _) => format!(
+                    "{} S{:02}E{:02}",
+                    episode.series_title, episode.season, episode.episode
+                ),
+                (None, Some(movie), _) => movie.title.clone(),
+                (None, None, Some(show)) => show.name.clone(),
+                (None, None, None) => entry.cleaned_title.clone(),
+            };
+            let year = entry
+                .release_year
+                .or_else(|| entry.movie.as_ref().and_then(|movie| movie.release_year()))
+                .map(|year| year.to_string())
+                .unwrap_or_default();
+            let link = match (&entry.movie, &entry.show, entry.tmdb_url.as_deref()) {
+                (None, None, _) => "(no match)".to_string(),
+                (_, _, Some(url)) => format!("[TMDB]({url})"),
+                (_, _, None) => String::new(),
+            };
+            markdown.push_str(&format!(
+                "| {date} | {} | {year} | {link} |\n",
+                escape_markdown_cell(&title)
+            ));
+        }
+    }
+    markdown
+}
+
+const HTML_HEAD: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>Watch history</title>
+<style>
+body { margin: 0; padding: 24px; background: #111; color: #eee;
+  font-family: system-ui, sans-serif; }
+h1 { font-weight: 600; }
+.grid { display: grid; grid-template-columns: repeat(auto-fill, minmax(160px, 1fr)); gap: 16px; }
+.card { color: inherit; text-decoration: none; background: #1c1c1c; border-radius: 8px;
+  overflow: hidden; }
+.card img, .placeholder { display: block; width: 100%; aspect-ratio: 2 / 3; object-fit: cover; }
+.placeholder { display: flex; align-items: center; justify-content: center; padding: 8px;
+  box-sizing: border-box; background: #2a2a2a; color: #888; text-align: center; }
+.card h2 { margin: 8px; font-size: 0.95rem; }
+.card p { margin: 0 8px 8px; font-size: 0.8rem; color: #aaa; }
+</style>
+</head>
+<body>
+<h1>Watch history</h1>
+<div class="grid">
+"#;
+
+const HTML_TAIL: &str = "</div>\n</body>\n</html>\n";
+
+/// Renders a standalone HTML page: a responsive grid of poster cards, each
+/// linking to TMDB when matched and titled with name and year. Entries
+/// without a poster get a placeholder card; overviews show as tooltips.
+pub fn to_html(entries: &[EnrichedEntry]) -> String {
+    let mut html = String::from(HTML_HEAD);
+    for entry in entries {
+        let title = escape_html(&display_title(entry));
+        let overview = entry.movie.as_ref().and_then(|movie| movie.overview.as_deref());
+        let tooltip = overview.map(|overview| format!(" title=\"{}\"", escape_html(overview)));
+        let poster = match entry.poster_url.as_deref() {
+            Some(url) => {
+                format!("<img src=\"{}\" alt=\"{title}\" loading=\"lazy\">", escape_html(url))
+            }
+            None => format!("<div class=\"placeholder\">{title}</div>"),
+        };
+        let date = entry
+            .watched_at
+            .as_deref()
+            .filter(|watched| parse_timestamp(watched).is_some())
+            .and_then(|watched| watched.get(..10))
+            .map(|date| format!("<p>{date}</p>"))
+            .unwrap_or_default();
+        let (open, close) = match entry.tmdb_url.as_deref() {
+            Some(url) => (format!("<a class=\"card\" href=\"{}\"", escape_html(url)), "</a>"),
+            None => ("<div class=\"card\"".to_string(), "</div>"),
+        };
+        html.push_str(&format!(
+            "{open}{}>{poster}<h2>{title}</h2>{date}{close}\n",
+            tooltip.unwrap_or_default()
+        ));
+    }
+    html.push_str(HTML_TAIL);
+    html
+}
+
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// Escapes pipes and flattens line breaks so a value stays in its table cell.
+fn escape_markdown_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+/// Quotes a CSV field when it holds a comma, quote or line break.
+fn escape_csv(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Every distinct title and year in `entries`, sorted so two lists can be
+/// diffed line by line. Titles sharing a cache key count as one (case,
+/// accents and a trailing article as in "Matrix, The" don't matter), keeping
+/// the first spelling seen; episodes list their series.
+pub fn title_year_list(entries: &[WatchEntry]) -> Vec<(String, Option<i32>)> {
+    let mut seen = HashSet::new();
+    let mut titles: Vec<(String, Option<i32>)> = entries
+        .iter()
+        .filter(|entry| !entry.cleaned_title.trim().is_empty())
+        .filter(|entry| seen.insert(cache_key(&entry.cleaned_title, entry.release_year)))
+        .map(|entry| (entry.cleaned_title.trim().to_string(), entry.release_year))
+        .collect();
+    titles.sort_by_cached_key(|(title, year)| (cache_key(title, None), *year));
+    titles
+}
+
+/// The matched TMDB title when there is one, otherwise the cleaned title, plus the year.
+fn display_title(entry: &EnrichedEntry) -> String {
+    let title = match (&entry.movie, &entry.show) {
+        (Some(movie), _) => movie.title.as_str(),
+        (None, Some(show)) => show.name.as_str(),
+        (None, None) => &entry.cleaned_title,
+    };
+    match entry.release_year {
+        Some(year) => format!("{title} ({year})"),
+        None => title.to_string(),
+    }
+}
+
+fn escape_ics(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// Splits content lines longer than 75 octets, as RFC 5545 requires.
+fn fold_ics_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut width = 0;
+    for ch in line.chars() {
+        if width + ch.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(ch);
+        width += ch.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::enriched;
+    use crate::tmdb::TmdbMovie;
+
+    fn entry(title: &str, watched_at: Option<&str>) -> EnrichedEntry {
+        EnrichedEntry {
+            release_year: Some(2021),
+            tmdb_url: Some("https://www.themoviedb.org/movie/438631".to_string()),
+            ..enriched(title, watched_at)
+        }
+    }
+
+    #[test]
+    fn renders_vevents_for_timestamped_entries() {
+        let entries = vec![
+            entry("Dune; Part One, Again", Some("2025-01-01T21:30:00+01:00")),
+            entry("Undated", None),
+        ];
+        let ics = to_ics(&entries);
+        let lines: Vec<&str> = ics.split("\r\n").collect();
+
+        assert_eq!(lines[0], "BEGIN:VCALENDAR");
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 1);
+        assert!(lines.contains(&"DTSTART:20250101T203000Z"));
+        assert!(lines.contains(&"SUMMARY:Dune\\; Part One\\, Again (2021)"));
+        assert!(lines.contains(&"URL:https://www.themoviedb.org/movie/438631"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(!ics.contains("Undated"));
+    }
+
+    #[test]
+    fn writes_letterboxd_csv() {
+        let late_night = Some("2025-01-04T23:30:00-05:00");
+        let mut matched = entry("Crouching Tiger, Hidden Dragon", late_night);
+        matched.release_year = Some(2000);
+        matched.movie = Some(TmdbMovie {
+            id: 146,
+            title: "Crouching Tiger, Hidden Dragon".to_string(),
+            original_title: None,
+            original_language: None,
+            overview: None,
+            release_date: Some("2000-07-06".to_string()),
+            poster_path: None,
+            backdrop_path: None,
+            raw: None,
+        });
+        let mut unmatched = entry("Some \"Home\" Video", None);
+        unmatched.release_year = None;
+        let mut episode = entry("The Office", Some("2025-01-05T20:00:00Z"));
+        episode.episode = Some(crate::Episode {
+            series_title: "The Office".to_string(),
+            season: 3,
+            episode: 5,
+        });
+
+        assert_eq!(
+            to_letterboxd_csv(&[matched, unmatched, episode]),
+            "Title,Year,WatchedDate,tmdbID\n\
+             \"Crouching Tiger, Hidden Dragon\",2000,2025-01-04,146\n\
+             \"Some \"\"Home\"\" Video\",,,\n"
+        );
+    }
+
+    #[test]
+    fn writes_a_markdown_journal_by_month() {
+        let mut dune = entry("Dune", Some("2025-02-01T20:00:00Z"));
+        dune.movie = Some(TmdbMovie {
+            id: 438631,
+            title: "Dune".to_string(),
+            original_title: None,
+            original_language: None,
+            overview: None,
+            release_date: Some("2021-09-15".to_string()),
+            poster_path: None,
+            backdrop_path: None,
+            raw: None,
+        });
+        let mut piped = entry("Either | Or", Some("2025-01-31T23:00:00Z"));
+        piped.release_year = None;
+        let undated = entry("Home Video", None);
+
+        assert_eq!(
+            to_markdown(&[dune, piped, undated]),
+            "# Watch history\n\
+             \n## 2025-01\n\n\
+             | Watched | Title | Year | TMDB |\n| --- | --- | --- | --- |\n\
+             | 2025-01-31 | Either \\| Or |  | (no match) |\n\
+             \n## 2025-02\n\n\
+             | Watched | Title | Year | TMDB |\n| --- | --- | --- | --- |\n\
+             | 2025-02-01 | Dune | 2021 | [TMDB](https://www.themoviedb.org/movie/438631) |\n\
+             \n## Unknown\n\n\
+             | Watched | Title | Year | TMDB |\n| --- | --- | --- | --- |\n\
+             |  | Home Video | 2021 | (no match) |\n"
+        );
+    }
+
+    #[test]
+    fn writes_an_escaped_html_poster_grid() {
+        let mut matched = entry("Tom & Jerry", Some("2025-01-04T20:00:00Z"));
+        matched.poster_url = Some("https://image.tmdb.org/t/p/w342/tj.jpg".to_string());
+        matched.movie = Some(TmdbMovie {
+            id: 587807,
+            title: "Tom & Jerry".to_string(),
+            original_title: None,
+            original_language: None,
+            overview: Some("A \"cat\" <and> a mouse".to_string()),
+            release_date: None,
+            poster_path: None,
+            backdrop_path: None,
+            raw: None,
+        });
+        let mut unmatched = entry("<script>alert(1)</script>", None);
+        unmatched.tmdb_url = None;
+
+        let html = to_html(&[matched, unmatched]);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains(
+            "<a class=\"card\" href=\"https://www.themoviedb.org/movie/438631\" \
+             title=\"A &quot;cat&quot; &lt;and&gt; a mouse\">"
+        ));
+        assert!(html.contains("<img src=\"https://image.tmdb.org/t/p/w342/tj.jpg\""));
+        assert!(html.contains("<h2>Tom &amp; Jerry (2021)</h2><p>2025-01-04</p></a>"));
+        assert!(html.contains(
+            "<div class=\"card\"><div class=\"placeholder\">\
+             &lt;script&gt;alert(1)&lt;/script&gt; (2021)</div>"
+        ));
+        assert!(!html.contains("<script>"));
+        assert!(html.ends_with("</html>\n"));
+    }
+
+    #[test]
+    fn lists_each_title_and_year_once() {
+        let entries: Vec<WatchEntry> = [
+            "Dune.2021.mkv",
+            "alien.1979.mkv",
+            "DUNE.2021.1080p.mkv",
+            "Dune.1984.mkv",
+            "Home.Movie.mkv",
+            "home.movie.mkv",
+            "Alien.1979.mkv",
+            "Amélie.2001.mkv",
+            "Amelie 2001.mkv",
+            "Matrix, The 1999.mkv",
+            "The.Matrix.1999.mkv",
+        ]
+        .into_iter()
+        .filter_map(crate::parse_log_line)
+        .collect();
+
+        assert_eq!(
+            title_year_list(&entries),
+            vec![
+                ("alien".to_string(), Some(1979)),
+                ("Amélie".to_string(), Some(2001)),
+                ("Dune".to_string(), Some(1984)),
+                ("Dune".to_string(), Some(2021)),
+                ("Home Movie".to_string(), None),
+                ("Matrix, The".to_string(), Some(1999)),
+            ]
+        );
+    }
+
+    #[test]
+    fn folds_long_lines() {
+        let folded = fold_ics_line(&format!("SUMMARY:{}", "x".repeat(100)));
+        let parts: Vec<&str> = folded.split("\r\n").collect();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].len(), 75);
+        assert!(parts[1].starts_with(' '));
+    }
+}
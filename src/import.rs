@@ -0,0 +1,163 @@
+use crate::parse_timestamp;
+use serde::Deserialize;
+
+/// One play in a Trakt history export (the `/sync/history` shape).
+#[derive(Debug, Deserialize)]
+struct TraktWatch {
+    watched_at: Option<String>,
+    movie: Option<TraktTitle>,
+    show: Option<TraktTitle>,
+    episode: Option<TraktEpisode>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TraktTitle {
+    title: String,
+    year: Option<i32>,
+    #[serde(default)]
+    ids: TraktIds,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TraktIds {
+    tmdb: Option<u32>,
+    imdb: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TraktEpisode {
+    season: u32,
+    number: u32,
+}
+
+/// Log lines made from a Trakt export, oldest watch first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TraktImport {
+    pub lines: Vec<String>,
+    /// Items that are neither a movie nor an episode of a show (a bare show
+    /// or season), so there is nothing to log.
+    pub skipped: usize,
+}
+
+/// Turns a Trakt history export into `watched_at|Title Year` log lines. The
+/// year is left unbracketed since the cleaner drops bracketed segments whole.
+/// Movies carry their TMDB or IMDb id as a `{tmdb-603}` hint so enrichment
+/// skips the search; episodes become `Show Year S01E02`.
+pub fn trakt_log_lines(json: &str) -> Result<TraktImport, serde_json::Error> {
+    let mut watches: Vec<TraktWatch> = serde_json::from_str(json)?;
+    // Exports list the newest play first, but logs are read oldest first.
+    watches.sort_by_key(|watch| {
+        watch
+            .watched_at
+            .as_deref()
+            .and_then(parse_timestamp)
+            .map(|watched| watched.to_unix())
+    });
+
+    let mut import = TraktImport::default();
+    for watch in watches {
+        let title = match (&watch.movie, &watch.show, &watch.episode) {
+            (Some(movie), _, _) => {
+                let hint = match (movie.ids.tmdb, movie.ids.imdb.as_deref()) {
+                    (Some(id), _) => format!(" {{tmdb-{id}}}"),
+                    (None, Some(id)) if id.starts_with("tt") => format!(" {{imdb-{id}}}"),
+                    _ => String::new(),
+                };
+                format!("{}{hint}", title_with_year(movie))
+            }
+            (None, Some(show), Some(episode)) => format!(
+                "{} S{:02}E{:02}",
+                title_with_year(show),
+                episode.season,
+                episode.number
+            ),
+            _ => {
+                import.skipped += 1;
+                continue;
+            }
+        };
+        import.lines.push(match watch.watched_at.as_deref().map(str::trim) {
+            Some(watched_at) if !watched_at.is_empty() => format!("{watched_at}|{title}"),
+            _ => title,
+        });
+    }
+    Ok(import)
+}
+
+fn title_with_year(title: &TraktTitle) -> String {
+    let name = log_safe_title(&title.title);
+    match title.year {
+        Some(year) => format!("{name} {year}"),
+        None => name,
+    }
+}
+
+/// The title with whatever would cut it short when read back as a file name
+/// (path separators, a `.` taken for an extension, the `|` delimiter,
+/// brackets the cleaner would drop) turned into spaces: "Mr. Robot" is
+/// logged as "Mr Robot".
+fn log_safe_title(title: &str) -> String {
+    let spaced: String = title
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | '.' | '|' | '(' | ')' | '[' | ']' | '{' | '}' => ' ',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect();
+    spaced.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_log_line, IdHint};
+
+    #[test]
+    fn trakt_history_becomes_log_lines_the_parser_reads_back() {
+        let export = r#"[
+            {"id": 3, "watched_at": "2024-03-02T21:00:00.000Z", "action": "scrobble",
+             "type": "episode",
+             "episode": {"season": 1, "number": 2, "title": "Cat's in the Bag...",
+                         "ids": {"trakt": 74}},
+             "show": {"title": "Breaking Bad", "year": 2008, "ids": {"tmdb": 1396}}},
+            {"id": 2, "watched_at": "2024-03-01T20:00:00.000Z", "action": "watch",
+             "type": "movie",
+             "movie": {"title": "Mr. Robot: Who/What (Redux)", "year": 2015,
+                       "ids": {"imdb": "tt0000001"}}},
+            {"id": 1, "watched_at": "2024-02-29T19:30:00.000Z", "action": "checkin",
+             "type": "movie",
+             "movie": {"title": "Dune", "year": 2021, "ids": {"trakt": 1, "tmdb": 438631}}},
+            {"id": 4, "watched_at": "2024-03-03T10:00:00.000Z", "type": "show",
+             "show": {"title": "Severance", "year": 2022}}
+        ]"#;
+
+        let import = trakt_log_lines(export).expect("valid export");
+        assert_eq!(
+            import.lines,
+            [
+                "2024-02-29T19:30:00.000Z|Dune 2021 {tmdb-438631}",
+                "2024-03-01T20:00:00.000Z|Mr Robot: Who What Redux 2015 {imdb-tt0000001}",
+                "2024-03-02T21:00:00.000Z|Breaking Bad 2008 S01E02",
+            ]
+        );
+        assert_eq!(import.skipped, 1);
+
+        let dune = parse_log_line(&import.lines[0]).expect("movie line");
+        assert_eq!(dune.watched_at.as_deref(), Some("2024-02-29T19:30:00.000Z"));
+        assert_eq!(dune.cleaned_title, "Dune");
+        assert_eq!(dune.release_year, Some(2021));
+        assert_eq!(dune.id_hint, Some(IdHint::Tmdb(438631)));
+        let robot = parse_log_line(&import.lines[1]).expect("bracketed title");
+        assert_eq!(robot.cleaned_title, "Mr Robot: Who What Redux");
+        assert_eq!(robot.release_year, Some(2015));
+
+        let episode = parse_log_line(&import.lines[2]).expect("episode line");
+        assert_eq!(episode.cleaned_title, "Breaking Bad");
+        assert_eq!(episode.release_year, Some(2008));
+        let episode = episode.episode.expect("episode");
+        assert_eq!((episode.season, episode.episode), (1, 2));
+
+        assert!(trakt_log_lines(r#"{"movies": []}"#).is_err());
+    }
+}
@@ -1,30 +1,98 @@
 use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
 use std::path::Path;
 use std::sync::OnceLock;
+use std::time::Duration;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
 
 pub mod tmdb;
 pub mod enrich;
 pub mod app;
+pub mod export;
+pub mod import;
+pub mod stats;
+#[cfg(test)]
+mod test_support;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Field names are part of the `--format ndjson` output; keep them stable.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub struct WatchEntry {
     pub watched_at: Option<String>,
     pub raw_title: String,
+    /// The logged path the title was taken from, with `file://` URIs decoded
+    /// back to a plain path; kept so the file can be played or located again.
+    pub source_path: Option<String>,
     pub cleaned_title: String,
     pub release_year: Option<i32>,
+    /// Network MRL with no filename to take a title from (live TV, radio, bare streams).
+    pub is_stream: bool,
+    /// Set for TV episodes; `cleaned_title` then holds just the series name.
+    pub episode: Option<Episode>,
+    /// An explicit `{tmdb-…}`/`{imdb-…}` id from the filename.
+    pub id_hint: Option<IdHint>,
+    /// Which file of a split release this is (`CD2`, `Disc 2`); see [`merge_parts`].
+    pub part: Option<u32>,
+    /// How far into the file playback got, from 0 to 1. Only JSON log lines
+    /// record it.
+    pub position: Option<f32>,
+    /// Length of the file in seconds. Only JSON log lines record it.
+    pub duration: Option<u32>,
 }
 
+/// Tricky filenames and the title the cleaner should produce for each.
+/// Shared by the unit tests and `goo self-test`.
+pub const CLEANER_CORPUS: &[(&str, &str)] = &[
+    ("Dune.2021.1080p.BluRay.x264.DTS.mkv", "Dune"),
+    ("The.Matrix.(1999).2160p.HDR.Remux.mkv", "The Matrix"),
+    ("Amores.Perros.2000.1080p.BluRay.x264.AAC5.1-[YTS.MX].mp4", "Amores Perros"),
+    ("Blade.Runner.2049.1080p.mkv", "Blade Runner 2049"),
+    ("Alien.1979.720p.mkv", "Alien"),
+    ("2001.A.Space.Odyssey.1968.1080p.mkv", "2001 A Space Odyssey"),
+    ("1917.mkv", "1917"),
+    ("Parasite (2019) [1080p] [BluRay] [5.1] [YTS.MX].mp4", "Parasite"),
+    ("Heat.1995.1080p.BluRay.x265.10bit.mkv", "Heat"),
+    ("Tenet2160p.HDR.mkv", "Tenet"),
+    ("1917.2019.1080p.mkv", "1917"),
+    ("2012.mkv", "2012"),
+    ("2001: A Space Odyssey.mkv", "2001: A Space Odyssey"),
+    ("www.TamilBlasters.com - Vikram.2022.1080p.WEB-DL.mkv", "Vikram"),
+    ("[SubsPlease][Sousou no Frieren][01][1080p].mkv", "Sousou no Frieren"),
+    ("The.Batman.2022.1080p.WEB-DL-GROUPNAME.mkv", "The Batman"),
+    ("www.1TamilMV.cx-Movie.2021.1080p.WEB-DL.mkv", "Movie"),
+    ("www.TamilMV.com-Vikram.2022.1080p.WEB-DL.x264-GRP.mkv", "Vikram"),
+];
+
 struct Cleaners {
+    extension: Regex,
+    glued_resolution: Regex,
     bracketed: Regex,
     audio_channels: Regex,
     fluff: Regex,
     separators: Regex,
+    separators_keep_hyphens: Regex,
     whitespace: Regex,
+    timestamp: Regex,
+    episode: Regex,
+    technical_tag: Regex,
+    id_hint: Regex,
+    disc_part: Regex,
+    trailing_part: Regex,
+    site_prefix: Regex,
 }
 
 fn cleaners() -> &'static Cleaners {
     static CLEANERS: OnceLock<Cleaners> = OnceLock::new();
     CLEANERS.get_or_init(|| Cleaners {
+        extension: Regex::new(
+            r"(?i)\.(?:mkv|mp4|m4v|avi|mov|wmv|webm|mpg|mpeg|ts|m2ts|flv|ogv|divx)$",
+        )
+        .expect("valid extension regex"),
+        glued_resolution: Regex::new(r"(?i)([a-z])?((?:480|576|720|1080|2160|4320)p)([a-z])?")
+            .expect("valid glued resolution regex"),
         bracketed: Regex::new(r"(?i)[\[\(\{].*?[\]\)\}]").expect("valid bracket regex"),
         audio_channels: Regex::new(
             r"(?i)\b(?:aac|ac3|eac3|ddp|dts|truehd|atmos|flac|opus|mp3|mp2)[\s._-]*\d\.\d\b",
@@ -35,40 +103,306 @@ fn cleaners() -> &'static Cleaners {
         )
         .expect("valid fluff regex"),
         separators: Regex::new(r"[._-]+").expect("valid separator regex"),
+        separators_keep_hyphens: Regex::new(r"[._]+").expect("valid separator regex"),
         whitespace: Regex::new(r"\s+").expect("valid whitespace regex"),
+        timestamp: Regex::new(
+            r"^(\d{4})-(\d{2})-(\d{2})(?:[T ](\d{2}):(\d{2})(?::(\d{2})(?:\.\d+)?)?(Z|[+-]\d{2}:?\d{2})?)?$",
+        )
+        .expect("valid timestamp regex"),
+        episode: Regex::new(
+            r"(?i)(?:^|[\s._-])(?:s(\d{1,2})[\s._-]?e(\d{1,3})|(\d{1,2})x(\d{2,3}))(?:$|[^0-9])",
+        )
+        .expect("valid episode regex"),
+        technical_tag: Regex::new(
+            r"(?i)^(?:\d{3,4}[pi]|[48]k|[xh]\.?26[45]|hevc|avc|aac\S*|e?ac3|ddp\S*|dts\S*|truehd|atmos|flac|bluray|blu-ray|brrip|bdrip|webrip|web-?dl|web|hdtv|dvdrip|hdr\S*|remux|\d+bit|[\d.]+)$",
+        )
+        .expect("valid technical tag regex"),
+        id_hint: Regex::new(r"(?i)\{(?:tmdb-(\d+)|imdb-(tt\d+))\}").expect("valid id hint regex"),
+        disc_part: Regex::new(
+            r"(?i)(?:^|[\s._\-\[(])(?:cd|dis[ck])[\s._-]?(\d{1,2})(?:[\])\s._-]|$)",
+        )
+        .expect("valid disc part regex"),
+        trailing_part: Regex::new(
+            r"(?i)([\s._\-(](?:19|20)\d{2}[\s._\-)]+)(?:part|pt)[\s._-]?(\d{1,2})(?:[\s._-]|$)",
+        )
+        .expect("valid trailing part regex"),
+        // `www.anything -`, or a bare `.com`/`.net`/`.org` domain set off by a
+        // spaced dash; other bare domains are too easily real titles. The
+        // `www.` labels stop at the first letters-only TLD followed by the
+        // dash, so `www.site.cx-Movie.WEB-DL` doesn't run on to `DL`.
+        site_prefix: Regex::new(
+            r"(?i)^\s*(?:www\.(?:[a-z0-9-]+\.)+?[a-z]{2,}\s*|[a-z0-9-]+(?:\.[a-z0-9-]+)*\.(?:com|net|org)\s)-\s*",
+        )
+        .expect("valid site prefix regex"),
     })
 }
 
 pub fn read_watch_log(path: &Path) -> std::io::Result<Vec<WatchEntry>> {
-    let content = match std::fs::read_to_string(path) {
-        Ok(content) => content,
-        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+    read_watch_log_with(path, &TitleCleaner::default())
+}
+
+/// [`read_watch_log`] with a custom cleaner, e.g. from a [`CleanProfile`].
+pub fn read_watch_log_with(path: &Path, cleaner: &TitleCleaner) -> std::io::Result<Vec<WatchEntry>> {
+    let mut entries = Vec::new();
+    read_watch_log_streaming_with(path, cleaner, |entry| entries.push(entry))?;
+    Ok(entries)
+}
+
+/// Hands each parsed entry to `on_entry` while reading the log line by line,
+/// so only one line is held in memory however long the history is. A
+/// missing log yields no entries.
+pub fn read_watch_log_streaming(
+    path: &Path,
+    on_entry: impl FnMut(WatchEntry),
+) -> std::io::Result<()> {
+    read_watch_log_streaming_with(path, &TitleCleaner::default(), on_entry)
+}
+
+/// [`read_watch_log_streaming`] with a custom cleaner.
+pub fn read_watch_log_streaming_with(
+    path: &Path,
+    cleaner: &TitleCleaner,
+    mut on_entry: impl FnMut(WatchEntry),
+) -> std::io::Result<()> {
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
         Err(err) => return Err(err),
     };
-    let mut entries = Vec::new();
-    for line in content.lines() {
-        if let Some(entry) = parse_log_line(line) {
-            entries.push(entry);
+    let mut reader = BufReader::new(file);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        if let Some(entry) = parse_log_line_with(&line, cleaner) {
+            on_entry(entry);
         }
     }
-    Ok(entries)
+}
+
+/// Collapses re-opens of the same title (VLC logs again on seeks and quick
+/// reopens) into the earliest watch. An entry is dropped when the same title,
+/// year and episode was logged no more than `window` before it; each dropped
+/// re-open extends the window, so a burst of seeks counts once. Entries
+/// without a parseable timestamp are always kept.
+pub fn merge_within(entries: Vec<WatchEntry>, window: Duration) -> Vec<WatchEntry> {
+    let window = window.as_secs() as i64;
+    let mut last_seen: HashMap<WatchKey, i64> = HashMap::new();
+    let mut merged = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let Some(at) = entry.watched_at.as_deref().and_then(parse_timestamp) else {
+            merged.push(entry);
+            continue;
+        };
+        let at = at.to_unix();
+        let key = watch_key(&entry);
+        let reopened = last_seen
+            .insert(key, at)
+            .is_some_and(|previous| (0..=window).contains(&(at - previous)));
+        if !reopened {
+            merged.push(entry);
+        }
+    }
+    merged
+}
+
+/// [`read_watch_log`] with runs of the same watch collapsed by [`dedup_consecutive`].
+pub fn read_watch_log_deduped(path: &Path) -> std::io::Result<Vec<WatchEntry>> {
+    read_watch_log(path).map(dedup_consecutive)
+}
+
+/// Collapses adjacent entries for the same title, year and episode into the
+/// first (earliest) of them, as VLC logs a file again on seeks and restarts.
+/// Repeats with another title in between, or logged on a different day, are
+/// kept as rewatches.
+pub fn dedup_consecutive(entries: Vec<WatchEntry>) -> Vec<WatchEntry> {
+    let mut deduped: Vec<WatchEntry> = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let repeat = deduped.last().is_some_and(|previous| {
+            watch_key(previous) == watch_key(&entry)
+                && watch_date(previous) == watch_date(&entry)
+        });
+        if !repeat {
+            deduped.push(entry);
+        }
+    }
+    deduped
+}
+
+/// Folds the files of a split release (`CD1`, `CD2`, ...) into one watch of
+/// the whole film. Parts merge into the entry before them when the title,
+/// year and day match and the part number goes up; the merged entry keeps the
+/// first part's timestamp and has its `part` cleared. A part logged on its
+/// own, or out of order, stays as it is.
+pub fn merge_parts(entries: Vec<WatchEntry>) -> Vec<WatchEntry> {
+    let mut merged: Vec<WatchEntry> = Vec::with_capacity(entries.len());
+    let mut last_part = None;
+    for entry in entries {
+        let continues = merged.last().is_some_and(|previous| {
+            title_key(previous) == title_key(&entry)
+                && watch_date(previous) == watch_date(&entry)
+        });
+        match (last_part, entry.part) {
+            (Some(previous), Some(part)) if continues && part > previous => {
+                last_part = Some(part);
+                if let Some(previous) = merged.last_mut() {
+                    previous.part = None;
+                }
+            }
+            (_, part) => {
+                last_part = part;
+                merged.push(entry);
+            }
+        }
+    }
+    merged
+}
+
+type TitleKey = (String, Option<i32>, Option<(u32, u32)>);
+type WatchKey = (TitleKey, Option<u32>);
+
+/// What makes two entries the same film or episode: title (ignoring case), year and episode.
+fn title_key(entry: &WatchEntry) -> TitleKey {
+    (
+        entry.cleaned_title.to_lowercase(),
+        entry.release_year,
+        entry.episode.as_ref().map(|episode| (episode.season, episode.episode)),
+    )
+}
+
+/// What makes two entries the same watch: the [`title_key`] and the part, so
+/// `CD1` and `CD2` are never mistaken for a re-open.
+fn watch_key(entry: &WatchEntry) -> WatchKey {
+    (title_key(entry), entry.part)
+}
+
+/// The UTC calendar day of the watch, when it has a timestamp.
+fn watch_date(entry: &WatchEntry) -> Option<(i64, u32, u32)> {
+    let at = parse_timestamp(entry.watched_at.as_deref()?)?;
+    Some((at.year, at.month, at.day))
+}
+
+/// Parses the last line of the log that holds a watch, skipping trailing `#`
+/// notes, sample files and anything else [`parse_log_line`] rejects. It reads
+/// backwards from the end so the cost doesn't grow with the size of the history.
+pub fn read_last_entry(path: &Path) -> std::io::Result<Option<WatchEntry>> {
+    let mut file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    last_parsed_line(&mut file, parse_log_line)
+}
+
+/// Walks the lines of `reader` from the end, returning the first that `parse`
+/// accepts.
+fn last_parsed_line<R: Read + Seek, T>(
+    reader: &mut R,
+    parse: impl Fn(&str) -> Option<T>,
+) -> std::io::Result<Option<T>> {
+    const CHUNK: u64 = 4096;
+
+    let mut end = reader.seek(SeekFrom::End(0))?;
+    let mut tail = Vec::new();
+    while end > 0 {
+        let start = end.saturating_sub(CHUNK);
+        let mut chunk = vec![0; (end - start) as usize];
+        reader.seek(SeekFrom::Start(start))?;
+        reader.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&tail);
+        tail = chunk;
+        end = start;
+
+        // Only trust a line once its start is in the buffer.
+        while let Some(newline) = tail.iter().rposition(|byte| *byte == b'\n') {
+            if let Some(parsed) = parse(&String::from_utf8_lossy(&tail[newline + 1..])) {
+                return Ok(Some(parsed));
+            }
+            tail.truncate(newline);
+        }
+        if end == 0 {
+            return Ok(parse(&String::from_utf8_lossy(&tail)));
+        }
+    }
+    Ok(None)
 }
 
 pub fn parse_log_line(line: &str) -> Option<WatchEntry> {
+    parse_log_line_with(line, &TitleCleaner::default())
+}
+
+pub fn parse_log_line_with(line: &str, cleaner: &TitleCleaner) -> Option<WatchEntry> {
     let trimmed = line.trim();
-    if trimmed.is_empty() {
+    // `#` lines are hand-written annotations, skipped like blank lines.
+    if trimmed.is_empty() || trimmed.starts_with('#') {
         return None;
     }
 
-    let (watched_at, raw) = split_log_line(trimmed);
-    let title_source = extract_title(raw);
-    let (cleaned, release_year) = clean_title_and_year(&title_source);
+    let json_line = json_log_line(trimmed);
+    let (watched_at, raw) = match &json_line {
+        Some(line) => (line.watched_at.as_deref(), line.path.as_str()),
+        None => split_log_line(trimmed),
+    };
+    let position = json_line.as_ref().and_then(|line| line.position);
+    let duration = json_line.as_ref().and_then(|line| line.duration);
+    let source_path = Some(decode_source_path(raw));
+    let (title_source, is_stream) = match stream_title(raw) {
+        Some(stream) => stream,
+        None => (extract_title(raw), false),
+    };
+    if cleaner.skip_extras && !is_stream && is_sample_or_trailer(&title_source) {
+        return None;
+    }
+    let id_hint = parse_id_hint(&title_source);
+    let unhinted = cleaners().id_hint.replace_all(&title_source, " ");
+    let (unhinted, part) = split_part(&unhinted);
+    let (cleaned, release_year, episode) = match split_episode(&unhinted, cleaner) {
+        Some((episode, year)) => (episode.series_title.clone(), year, Some(episode)),
+        None => {
+            let (cleaned, year) = cleaner.clean_with_year(&unhinted);
+            (cleaned, year, None)
+        }
+    };
 
     Some(WatchEntry {
         watched_at: watched_at.map(|value| value.to_string()),
         raw_title: title_source,
+        source_path,
         cleaned_title: cleaned,
         release_year,
+        is_stream,
+        episode,
+        id_hint,
+        part,
+        position,
+        duration,
+    })
+}
+
+/// A `sample` or `trailer` file rather than the film: the word comes right
+/// after the year or a release tag (`Movie.2021.Trailer.1080p.mkv`), ends a
+/// name that already has its year (`Movie.2021.Extended.Sample.mkv`), or ends
+/// it in lowercase the way scene samples are named (`something.sample.mkv`).
+/// As a title word ("Free Sample 2012", `The.Sample.mkv`) it doesn't count.
+pub fn is_sample_or_trailer(raw: &str) -> bool {
+    let cleaners = cleaners();
+    let stem = cleaners.extension.replace(raw.trim(), "");
+    let tokens: Vec<&str> = stem
+        .split([' ', '.', '_', '-', '(', ')', '[', ']'])
+        .filter(|token| !token.is_empty())
+        .collect();
+    // The first token is always title, even when it looks like a year (`1917`).
+    tokens.iter().enumerate().skip(1).any(|(idx, token)| {
+        let extra = token.eq_ignore_ascii_case("sample") || token.eq_ignore_ascii_case("trailer");
+        let last = idx == tokens.len() - 1;
+        let previous = tokens[idx - 1];
+        let after_tag = idx > 1
+            && (year_value(previous).is_some() || cleaners.technical_tag.is_match(previous));
+        let has_year = tokens[1..idx].iter().any(|token| year_value(token).is_some());
+        let scene_name = matches!(*token, "sample" | "trailer");
+        extra && (after_tag || (last && (has_year || scene_name)))
     })
 }
 
@@ -77,86 +411,814 @@ pub fn clean_title(raw: &str) -> String {
     cleaned
 }
 
-fn clean_title_and_year(raw: &str) -> (String, Option<i32>) {
+/// Explains why a cleaned title looks wrong, or `None` if it seems plausible.
+pub fn title_warning(cleaned: &str) -> Option<&'static str> {
+    if cleaned.trim().is_empty() {
+        return Some("empty title");
+    }
+    if cleaners().fluff.is_match(cleaned) {
+        return Some("release tag survived cleaning");
+    }
+    None
+}
+
+/// Groups that tag releases with a bare token instead of a `-GROUP` suffix.
+const KNOWN_RELEASE_GROUPS: &[&str] =
+    &["yify", "yts", "rarbg", "etrg", "pahe", "tigole", "qxr", "joy", "sparks"];
+
+/// The release group a filename credits, if one can be told apart from the title.
+///
+/// Tried in order: a leading `[Group]` (fansub style), a trailing `-GROUP` on
+/// a name that already carries release tags, the last bracketed tag that
+/// isn't technical (`[YTS.MX]`), and finally a bare well-known group token.
+pub fn release_group(raw: &str) -> Option<String> {
     let cleaners = cleaners();
-    let mut value = raw.trim().to_string();
-    
-    value = cleaners.bracketed.replace_all(&value, " ").to_string();
-    
-    // Process fluff BEFORE separators
-    value = cleaners.audio_channels.replace_all(&value, " ").to_string();
-    value = cleaners.fluff.replace_all(&value, " ").to_string();
-    value = cleaners.separators.replace_all(&value, " ").to_string();
-    value = cleaners.whitespace.replace_all(&value, " ").to_string();
-    
-    let tokens: Vec<&str> = value.split_whitespace().collect();
-    if tokens.is_empty() {
-        return (String::new(), None);
-    }
-
-    let current_year = current_year();
-    let mut keep = vec![true; tokens.len()];
-    let mut year_positions = Vec::new();
-    for (idx, token) in tokens.iter().enumerate() {
-        if is_year_token(token, current_year) {
-            year_positions.push(idx);
-        }
-    }
-
-    let mut release_year = None;
-    if !year_positions.is_empty() {
-        let last_idx = *year_positions.last().unwrap();
-        if year_positions.len() == 1 {
-            if last_idx != 0 {
+    let name = cleaners.extension.replace(raw.trim(), "");
+    let name = name.trim();
+
+    if let Some((group, _)) = name.strip_prefix('[').and_then(|rest| rest.split_once(']')) {
+        if is_group_tag(group) {
+            return Some(group.trim().to_string());
+        }
+    }
+
+    if let Some((before, group)) = name.rsplit_once('-') {
+        let scene_style = cleaners.fluff.is_match(before)
+            && !before.to_lowercase().ends_with("web")
+            && group.chars().all(|ch| ch.is_ascii_alphanumeric());
+        if scene_style && is_group_tag(group) {
+            return Some(group.to_string());
+        }
+    }
+
+    let bracketed = cleaners
+        .bracketed
+        .find_iter(name)
+        .map(|found| &found.as_str()[1..found.as_str().len() - 1])
+        .filter(|group| is_group_tag(group))
+        .last();
+    if let Some(group) = bracketed {
+        return Some(group.trim().to_string());
+    }
+
+    name.split(|ch: char| !ch.is_alphanumeric())
+        .find(|token| KNOWN_RELEASE_GROUPS.contains(&token.to_lowercase().as_str()))
+        .map(|token| token.to_string())
+}
+
+/// A single token with a letter in it that isn't a resolution, codec or source tag.
+fn is_group_tag(value: &str) -> bool {
+    let value = value.trim();
+    !value.is_empty()
+        && !value.contains(char::is_whitespace)
+        && value.chars().any(|ch| ch.is_alphabetic())
+        && !cleaners().technical_tag.is_match(value)
+}
+
+/// A database id pinned in the filename, Plex/Jellyfin style, so colliding
+/// titles can be matched exactly instead of searched.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdHint {
+    /// `{tmdb-603}`
+    Tmdb(u32),
+    /// `{imdb-tt0133093}`
+    Imdb(String),
+}
+
+/// Finds a `{tmdb-12345}` or `{imdb-tt123}` hint anywhere in a filename.
+pub fn parse_id_hint(raw: &str) -> Option<IdHint> {
+    let captures = cleaners().id_hint.captures(raw)?;
+    match (captures.get(1), captures.get(2)) {
+        (Some(tmdb), _) => tmdb.as_str().parse().ok().map(IdHint::Tmdb),
+        (None, Some(imdb)) => Some(IdHint::Imdb(imdb.as_str().to_ascii_lowercase())),
+        (None, None) => None,
+    }
+}
+
+/// Finds the part number of a split release: `CD1`, `Disc 2` anywhere, or a
+/// `Part 2`/`pt2` after the release year. A `Part 1` before the year is left
+/// alone, since it is usually the title's own ("Deathly Hallows Part 1").
+pub fn parse_part(raw: &str) -> Option<u32> {
+    split_part(raw).1
+}
+
+fn split_part(raw: &str) -> (String, Option<u32>) {
+    let cleaners = cleaners();
+    if let Some(captures) = cleaners.disc_part.captures(raw) {
+        let part = captures[1].parse().ok();
+        return (cleaners.disc_part.replace(raw, " ").into_owned(), part);
+    }
+    if let Some(captures) = cleaners.trailing_part.captures(raw) {
+        let part = captures[2].parse().ok();
+        return (cleaners.trailing_part.replace(raw, "${1} ").into_owned(), part);
+    }
+    (raw.to_string(), None)
+}
+
+/// Season and episode numbers found in a filename, with the series name before them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Episode {
+    pub series_title: String,
+    pub season: u32,
+    pub episode: u32,
+}
+
+/// Recognizes `S01E02`, `S01.E02` and `1x02` markers. The series title is the
+/// cleaned text in front of the marker.
+pub fn parse_episode(raw: &str) -> Option<Episode> {
+    split_episode(raw, &TitleCleaner::default()).map(|(episode, _)| episode)
+}
+
+/// [`parse_episode`] plus the year found in the series part (`Doctor.Who.2005.S01E01`).
+fn split_episode(raw: &str, cleaner: &TitleCleaner) -> Option<(Episode, Option<i32>)> {
+    let captures = cleaners().episode.captures(raw)?;
+    let (season, episode) = match (captures.get(1), captures.get(2)) {
+        (Some(season), Some(episode)) => (season, episode),
+        _ => (captures.get(3)?, captures.get(4)?),
+    };
+    let (series_title, year) = cleaner.clean_with_year(&raw[..captures.get(0)?.start()]);
+    if series_title.is_empty() {
+        return None;
+    }
+    let episode = Episode {
+        series_title,
+        season: season.as_str().parse().ok()?,
+        episode: episode.as_str().parse().ok()?,
+    };
+    Some((episode, year))
+}
+
+/// [`clean_title`] that also strips `extra` as release tags. Builds its
+/// pattern on every call; keep a [`TitleCleaner::with_extra_fluff`] around
+/// when cleaning many titles.
+pub fn clean_title_with_extra(raw: &str, extra: &[&str]) -> String {
+    TitleCleaner::default().with_extra_fluff(extra).clean(raw)
+}
+
+fn clean_title_and_year(raw: &str) -> (String, Option<i32>) {
+    clean_title_and_year_with_year(raw, current_year())
+}
+
+/// The cleaned title and release year, judging which years are plausible
+/// against `current_year` rather than the system clock.
+pub fn clean_title_and_year_with_year(raw: &str, current_year: i32) -> (String, Option<i32>) {
+    TitleCleaner::default().clean_with_year_as_of(raw, current_year)
+}
+
+/// The title with accents stripped and ligatures spelled out ("Amélie" ->
+/// "Amelie"), or `None` when it is already plain ASCII or has nothing to fold.
+/// Only used as a last-resort search query; displayed titles keep their accents.
+pub fn ascii_fold(title: &str) -> Option<String> {
+    if title.is_ascii() {
+        return None;
+    }
+    let mut folded = String::with_capacity(title.len());
+    for ch in title.nfd().filter(|ch| !is_combining_mark(*ch)) {
+        match ch {
+            'ß' => folded.push_str("ss"),
+            'æ' => folded.push_str("ae"),
+            'Æ' => folded.push_str("AE"),
+            'œ' => folded.push_str("oe"),
+            'Œ' => folded.push_str("OE"),
+            'ø' => folded.push('o'),
+            'Ø' => folded.push('O'),
+            'đ' => folded.push('d'),
+            'Đ' => folded.push('D'),
+            'ł' => folded.push('l'),
+            'Ł' => folded.push('L'),
+            _ => folded.push(ch),
+        }
+    }
+    (folded != title).then_some(folded)
+}
+
+/// Years this far past the current one still look like a mislabeled release
+/// year rather than part of a title ("Blade Runner 2049", "2046").
+const FUTURE_YEAR_HORIZON: i32 = 10;
+
+/// Configurable title cleaning. `TitleCleaner::default()` is what
+/// [`clean_title`] and [`parse_log_line`] use.
+///
+/// Year policy: a 4-digit token from 1900 up to `current_year +
+/// future_year_window` is a release year. A later year-shaped token (up to
+/// [`FUTURE_YEAR_HORIZON`] years out) that trails the title is still
+/// stripped, since it is almost always a mislabeled year, but it is not
+/// reported as the release year. Anything further out is kept as title text.
+///
+/// The remaining switches pick which stages run; [`CleanProfile`] has presets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TitleCleaner {
+    pub future_year_window: i32,
+    /// Drop bracketed segments and resolution/codec/source tags.
+    pub strip_tags: bool,
+    /// Treat `-` as a word separator. Off keeps `Spider-Man` intact.
+    pub split_hyphens: bool,
+    /// Drop release-group tokens trailing the last release tag (`-GROUP`,
+    /// `[GROUP]`, a bare `FLUX`), whatever the group.
+    pub strip_release_group: bool,
+    /// Drop every word trailing the last release tag, not just group-like
+    /// ones, since scene names put nothing but metadata there.
+    pub strip_scene_tail: bool,
+    /// Pull a release year out of the title.
+    pub strip_years: bool,
+    /// Skip log lines for sample and trailer files; see [`is_sample_or_trailer`].
+    pub skip_extras: bool,
+    /// The user's own release tags, stripped along with the built-in ones.
+    pub extra_fluff: FluffList,
+    /// When dropping bracketed segments would leave no title, keep the
+    /// longest one instead, as in `[Group][Title][01]` anime releases.
+    pub bracket_title_fallback: bool,
+}
+
+/// Extra words for [`TitleCleaner`] to strip as release tags, matched whole
+/// and ignoring case. Empty by default, so the shared built-in patterns are
+/// all that run.
+#[derive(Debug, Clone, Default)]
+pub struct FluffList {
+    words: Vec<String>,
+    pattern: Option<Regex>,
+}
+
+impl FluffList {
+    /// Escapes each word, so `DD+` or `[HC]` match literally.
+    pub fn new<I, S>(words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let words: Vec<String> = words
+            .into_iter()
+            .map(|word| word.as_ref().trim().to_string())
+            .filter(|word| !word.is_empty())
+            .collect();
+        if words.is_empty() {
+            return Self::default();
+        }
+        // `\b` only holds next to a word character, so an edge that is
+        // punctuation (`[HC]`) goes without one.
+        let boundary = |ch: Option<char>| match ch {
+            Some(ch) if ch.is_alphanumeric() || ch == '_' => r"\b",
+            _ => "",
+        };
+        let alternatives: Vec<String> = words
+            .iter()
+            .map(|word| {
+                let start = boundary(word.chars().next());
+                let end = boundary(word.chars().last());
+                format!("{start}{}{end}", regex::escape(word))
+            })
+            .collect();
+        let pattern = format!("(?i){}", alternatives.join("|"));
+        Self {
+            pattern: Some(Regex::new(&pattern).expect("escaped fluff words form a valid regex")),
+            words,
+        }
+    }
+
+    pub fn words(&self) -> &[String] {
+        &self.words
+    }
+
+    fn strip(&self, value: &str) -> String {
+        match &self.pattern {
+            Some(pattern) => pattern.replace_all(value, " ").to_string(),
+            None => value.to_string(),
+        }
+    }
+}
+
+impl PartialEq for FluffList {
+    fn eq(&self, other: &Self) -> bool {
+        self.words == other.words
+    }
+}
+
+impl Eq for FluffList {}
+
+impl Default for TitleCleaner {
+    fn default() -> Self {
+        CleanProfile::default().cleaner()
+    }
+}
+
+/// Cleaning presets for the naming conventions libraries tend to follow.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CleanProfile {
+    /// Mixed libraries: groups after the release tags are dropped, other words there kept.
+    #[default]
+    Standard,
+    /// Scene names like `Heat.1995.1080p.BluRay.x264-GROUP`; everything after the tags goes.
+    Scene,
+    /// Renamed files like `Spider-Man (2002).mkv`; hyphens belong to the title.
+    Plex,
+    /// Only the extension and `.`/`_` separators are touched, and every line is kept.
+    Raw,
+}
+
+impl CleanProfile {
+    pub const ALL: [CleanProfile; 4] =
+        [CleanProfile::Standard, CleanProfile::Scene, CleanProfile::Plex, CleanProfile::Raw];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CleanProfile::Standard => "standard",
+            CleanProfile::Scene => "scene",
+            CleanProfile::Plex => "plex",
+            CleanProfile::Raw => "raw",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|profile| profile.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    pub fn cleaner(self) -> TitleCleaner {
+        let standard = TitleCleaner {
+            future_year_window: 1,
+            strip_tags: true,
+            split_hyphens: true,
+            strip_release_group: true,
+            strip_scene_tail: false,
+            strip_years: true,
+            skip_extras: true,
+            extra_fluff: FluffList::default(),
+            bracket_title_fallback: true,
+        };
+        match self {
+            CleanProfile::Standard => standard,
+            CleanProfile::Scene => TitleCleaner {
+                strip_scene_tail: true,
+                ..standard
+            },
+            CleanProfile::Plex => TitleCleaner {
+                split_hyphens: false,
+                ..standard
+            },
+            CleanProfile::Raw => TitleCleaner {
+                strip_tags: false,
+                split_hyphens: false,
+                strip_years: false,
+                skip_extras: false,
+                ..standard
+            },
+        }
+    }
+}
+
+impl TitleCleaner {
+    /// Accept release years up to `years` past the current one (e.g. for screeners).
+    pub fn with_future_year_window(mut self, years: i32) -> Self {
+        self.future_year_window = years.max(0);
+        self
+    }
+
+    /// Also strip `words` wherever they appear whole, e.g. local dub tags
+    /// like `Dublado` or tags newer than the built-in list.
+    pub fn with_extra_fluff<I, S>(mut self, words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.extra_fluff = FluffList::new(words);
+        self
+    }
+
+    pub fn clean(&self, raw: &str) -> String {
+        self.clean_with_year(raw).0
+    }
+
+    pub fn clean_with_year(&self, raw: &str) -> (String, Option<i32>) {
+        self.clean_with_year_as_of(raw, current_year())
+    }
+
+    /// [`TitleCleaner::clean_with_year`] with the year policy measured from
+    /// `current_year` instead of the system clock.
+    pub fn clean_with_year_as_of(&self, raw: &str, current_year: i32) -> (String, Option<i32>) {
+        let (cleaned, release_year, _) = self.clean_release(raw, current_year);
+        (cleaned, release_year)
+    }
+
+    /// Cleans `raw` and keeps what the cleaner stripped as [`WatchMetadata`].
+    pub fn parse_release(&self, raw: &str) -> (String, WatchMetadata) {
+        let (cleaned, _, metadata) = self.clean_release(raw, current_year());
+        (cleaned, metadata)
+    }
+
+    fn clean_release(
+        &self,
+        raw: &str,
+        current_year: i32,
+    ) -> (String, Option<i32>, WatchMetadata) {
+        let cleaners = cleaners();
+        // macOS hands out NFD names ("Ame\u{301}lie"); compose them so the
+        // title and its cache key match what other platforms write.
+        let raw: String = raw.nfc().collect();
+        let raw = raw.as_str();
+        let mut metadata = WatchMetadata {
+            release_group: release_group(raw),
+            ..WatchMetadata::default()
+        };
+        let mut value = cleaners.extension.replace(raw.trim(), "").to_string();
+
+        if self.strip_tags {
+            value = self.strip_release_tags(&value, &mut metadata);
+            value = self.extra_fluff.strip(&value);
+        }
+        let separators = if self.split_hyphens {
+            &cleaners.separators
+        } else {
+            &cleaners.separators_keep_hyphens
+        };
+        value = separators.replace_all(&value, " ").to_string();
+        value = cleaners.whitespace.replace_all(&value, " ").to_string();
+
+        // With hyphens kept, `Heat - Director's Cut` leaves a lone `-` behind.
+        let tokens: Vec<&str> = value
+            .split_whitespace()
+            .filter(|token| !token.chars().all(|ch| ch == '-'))
+            .collect();
+        if tokens.is_empty() {
+            return (String::new(), None, metadata);
+        }
+        if !self.strip_years {
+            return (tokens.join(" "), None, metadata);
+        }
+
+        let latest_year = current_year + self.future_year_window;
+        let mut keep = vec![true; tokens.len()];
+        let mut year_positions = Vec::new();
+        for (idx, token) in tokens.iter().enumerate() {
+            if is_year_token(token, latest_year) {
+                year_positions.push(idx);
+            }
+        }
+
+        // The last year is the release year unless it leads the name: then it
+        // is the title ("2012", "1917"), or the start of it ("2001 A Space
+        // Odyssey"). A lone year is never a release year, while a repeated one
+        // is a title-year followed by its release year ("1984 1984"). "2001:"
+        // never counts as a year, so colon titles stay whole.
+        let mut release_year = None;
+        if let Some(&last_idx) = year_positions.last() {
+            if last_idx > 0 {
                 keep[last_idx] = false;
                 release_year = tokens[last_idx].parse::<i32>().ok();
             }
-        } else {
+        }
+
+        let last_idx = tokens.len() - 1;
+        let trailing_future_year = year_value(tokens[last_idx])
+            .is_some_and(|year| year > latest_year && year <= current_year + FUTURE_YEAR_HORIZON);
+        if last_idx > 0 && trailing_future_year {
             keep[last_idx] = false;
-            release_year = tokens[last_idx].parse::<i32>().ok();
         }
+
+        let mut cleaned = Vec::new();
+        for (idx, token) in tokens.iter().enumerate() {
+            if keep[idx] {
+                cleaned.push(*token);
+            }
+        }
+
+        (cleaned.join(" "), release_year, metadata)
+    }
+
+    /// Drops bracketed segments and release tags, recording what they were.
+    fn strip_release_tags(&self, value: &str, metadata: &mut WatchMetadata) -> String {
+        let cleaners = cleaners();
+
+        // `www.TamilBlasters.com - Movie.2021`: drop the site the file came from.
+        let value = match cleaners.site_prefix.find(value) {
+            Some(prefix) if prefix.end() < value.len() => &value[prefix.end()..],
+            _ => value,
+        };
+
+        // `Movie2160p` / `1080pBluRay`: split resolutions glued to a word so the
+        // fluff pass can see them. Digits before the tag are left alone.
+        let value = cleaners.glued_resolution.replace_all(value, "${1} ${2} ${3}");
+
+        let unbracketed = cleaners
+            .bracketed
+            .replace_all(&value, |caps: &regex::Captures| {
+                for tag in cleaners.fluff.find_iter(&caps[0]) {
+                    metadata.record(tag.as_str());
+                }
+                " "
+            })
+            .to_string();
+        let blanked = !unbracketed.chars().any(char::is_alphanumeric);
+        let value = if self.bracket_title_fallback && blanked {
+            bracket_title(&value).unwrap_or(unbracketed)
+        } else {
+            unbracketed
+        };
+
+        // Process fluff BEFORE separators
+        let mut value = cleaners
+            .audio_channels
+            .replace_all(&value, |caps: &regex::Captures| {
+                metadata.record(&caps[0]);
+                " "
+            })
+            .to_string();
+        let group_start = scene_tail_start(&value, false).filter(|_| self.strip_release_group);
+        let tail_start = group_start
+            .or_else(|| scene_tail_start(&value, true).filter(|_| self.strip_scene_tail));
+        if let Some(start) = tail_start {
+            if metadata.release_group.is_none() && group_start.is_some() {
+                let mut tail = value[start..].rsplit([' ', '.', '_', '-']);
+                let group = tail.find(|token| !token.is_empty());
+                metadata.release_group = group.map(str::to_string);
+            }
+            value.truncate(start);
+        }
+        cleaners
+            .fluff
+            .replace_all(&value, |caps: &regex::Captures| {
+                metadata.record(&caps[0]);
+                " "
+            })
+            .to_string()
     }
+}
 
-    let mut cleaned = Vec::new();
-    for (idx, token) in tokens.iter().enumerate() {
-        if keep[idx] {
-            cleaned.push(*token);
+/// The longest bracketed segment once release tags are taken out of it, for
+/// names made only of brackets (`[Group][Title][01]`). Segments without a
+/// letter, like episode numbers, never count.
+fn bracket_title(value: &str) -> Option<String> {
+    let cleaners = cleaners();
+    let mut best: Option<String> = None;
+    for segment in cleaners.bracketed.find_iter(value) {
+        let inner = &segment.as_str()[1..segment.len() - 1];
+        let candidate = cleaners.fluff.replace_all(inner, " ").trim().to_string();
+        let longer = best
+            .as_ref()
+            .is_none_or(|best| candidate.chars().count() > best.chars().count());
+        if longer && candidate.chars().any(char::is_alphabetic) {
+            best = Some(candidate);
         }
     }
+    best
+}
+
+/// Where trailing scene metadata starts: text after the last release tag made
+/// only of group-like tokens (`-GROUPNAME`, `-Grym`, a bare `FLUX`). Nothing
+/// is cut unless a tag comes after some title text, and a tail with ordinary
+/// words ("Directors Cut") is kept whole.
+fn scene_tail_start(value: &str, any_words: bool) -> Option<usize> {
+    let fluff = &cleaners().fluff;
+    let first = fluff.find(value)?;
+    let last = fluff.find_iter(value).last()?;
+    if !value[..first.start()].chars().any(char::is_alphanumeric) {
+        return None;
+    }
+    let mut tokens = value[last.end()..]
+        .split([' ', '.', '_'])
+        .filter(|token| !token.chars().all(|ch| ch == '-'))
+        .peekable();
+    tokens.peek()?;
+    (any_words || tokens.all(is_group_token)).then_some(last.end())
+}
+
+/// `-Anything` that isn't a bare number, or an all-caps token like `FLUX` or `H`.
+fn is_group_token(token: &str) -> bool {
+    let body = token.trim_start_matches('-');
+    if body.is_empty() || body.chars().all(|ch| ch.is_ascii_digit()) {
+        return false;
+    }
+    if token.starts_with('-') {
+        return body.chars().all(|ch| ch.is_alphanumeric() || ch == '-');
+    }
+    body.chars().any(|ch| ch.is_ascii_uppercase())
+        && body
+            .chars()
+            .all(|ch| ch.is_ascii_uppercase() || ch.is_ascii_digit() || ch == '-')
+}
+
+/// Release details the cleaner strips from a filename, as written there
+/// (`1080p`, `x264`, `BluRay`). Each field keeps the first tag found.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct WatchMetadata {
+    pub resolution: Option<String>,
+    pub video_codec: Option<String>,
+    pub audio_codec: Option<String>,
+    /// Where the rip came from: `BluRay`, `WEBRip`, `WEB-DL`, ...
+    pub source: Option<String>,
+    pub release_group: Option<String>,
+}
+
+impl WatchMetadata {
+    fn record(&mut self, tag: &str) {
+        let lower = tag.to_lowercase();
+        let slot = match lower.as_str() {
+            "480p" | "720p" | "1080p" | "2160p" | "4k" | "8k" => &mut self.resolution,
+            "x264" | "x265" | "h264" | "h265" | "hevc" => &mut self.video_codec,
+            "bluray" | "brrip" | "webrip" | "web-dl" | "dvdrip" | "remux" => &mut self.source,
+            _ if ["aac", "ac3", "eac3", "ddp", "dts", "truehd", "atmos", "flac", "opus", "mp3"]
+                .iter()
+                .any(|codec| lower.starts_with(codec)) =>
+            {
+                // `AAC5.1` / `DTS-HD 5.1` -> `AAC` / `DTS`
+                let codec: String = tag.chars().take_while(|ch| ch.is_alphanumeric()).collect();
+                let codec = codec.trim_end_matches(|ch: char| ch.is_ascii_digit());
+                if self.audio_codec.is_none() && !codec.is_empty() {
+                    self.audio_codec = Some(codec.to_string());
+                }
+                return;
+            }
+            _ => return,
+        };
+        if slot.is_none() {
+            *slot = Some(tag.to_string());
+        }
+    }
+}
+
+/// [`clean_title`] that also returns the release details it stripped.
+pub fn parse_release(raw: &str) -> (String, WatchMetadata) {
+    TitleCleaner::default().parse_release(raw)
+}
+
+/// A line of the JSON-lines log format, e.g.
+/// `{"path": "file:///films/Heat.mkv", "timestamp": "2025-01-01T20:00:00Z"}`,
+/// optionally with the playback `position` (0 to 1) and `duration` (seconds)
+/// reached. Anything else on the line is ignored.
+#[derive(Deserialize)]
+struct JsonLogLine {
+    path: String,
+    #[serde(default)]
+    timestamp: Option<JsonTimestamp>,
+    #[serde(default)]
+    position: Option<f64>,
+    #[serde(default)]
+    duration: Option<f64>,
+}
+
+/// What a JSON log line says about one watch.
+struct JsonWatch {
+    watched_at: Option<String>,
+    path: String,
+    position: Option<f32>,
+    duration: Option<u32>,
+}
 
-    (cleaned.join(" "), release_year)
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonTimestamp {
+    Text(String),
+    /// Unix seconds.
+    Unix(i64),
+}
+
+/// A JSON log line, or `None` for a line in the delimited format (or JSON
+/// without a `path`), which is parsed as before. Out-of-range positions and
+/// durations are dropped rather than trusted.
+fn json_log_line(line: &str) -> Option<JsonWatch> {
+    if !line.starts_with('{') {
+        return None;
+    }
+    let parsed: JsonLogLine = serde_json::from_str(line).ok()?;
+    let watched_at = parsed.timestamp.map(|timestamp| match timestamp {
+        JsonTimestamp::Text(text) => text,
+        JsonTimestamp::Unix(seconds) => DateTime::from_unix(seconds).to_string(),
+    });
+    Some(JsonWatch {
+        watched_at,
+        path: parsed.path,
+        position: parsed
+            .position
+            .filter(|position| (0.0..=1.0).contains(position))
+            .map(|position| position as f32),
+        duration: parsed
+            .duration
+            .filter(|duration| duration.is_finite() && *duration > 0.0)
+            .map(|duration| duration.round() as u32),
+    })
 }
 
 fn split_log_line(line: &str) -> (Option<&str>, &str) {
     if let Some((left, right)) = line.split_once('|') {
-        return (Some(left.trim()), right.trim());
+        return order_fields(left.trim(), right.trim());
     }
 
     if let Some((left, right)) = line.split_once('\t') {
-        return (Some(left.trim()), right.trim());
+        return order_fields(left.trim(), right.trim());
     }
 
     (None, line)
 }
 
+/// Some custom loggers write `path|timestamp`; put the timestamp first again.
+fn order_fields<'a>(left: &'a str, right: &'a str) -> (Option<&'a str>, &'a str) {
+    let timestamp = &cleaners().timestamp;
+    if timestamp.is_match(right) && !timestamp.is_match(left) {
+        return (Some(right), left);
+    }
+    (Some(left), right)
+}
+
+/// The logged value with a `file://` URI turned back into a plain path
+/// (`file:///C:/Films/Am%C3%A9lie.mkv` -> `C:/Films/Amélie.mkv`, and
+/// `file://nas/share/...` -> `//nas/share/...`). Plain paths and network MRLs
+/// are kept as logged.
+fn decode_source_path(raw: &str) -> String {
+    let trimmed = raw.trim();
+    let Some(uri_path) = trimmed.strip_prefix("file://") else {
+        return trimmed.to_string();
+    };
+    let path = percent_decode(uri_path);
+    if !path.starts_with('/') {
+        return format!("//{path}");
+    }
+    // `file:///C:/...` leaves a slash in front of the drive letter.
+    let bytes = path.as_bytes();
+    if bytes.len() >= 3 && bytes[0] == b'/' && bytes[1].is_ascii_alphabetic() && bytes[2] == b':' {
+        return path[1..].to_string();
+    }
+    path
+}
+
 fn extract_title(raw: &str) -> String {
     let trimmed = raw.trim();
-    let without_prefix = trimmed.strip_prefix("file:///").unwrap_or(trimmed);
-    let path = Path::new(without_prefix);
+    let uri_path = trimmed.strip_prefix("file://");
+    let without_prefix = uri_path.unwrap_or(trimmed);
+    // Logs written on Windows use `\` even when read elsewhere.
+    let file_name = without_prefix
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or(without_prefix);
+    // Only URIs are percent-encoded; a plain path may contain a literal `%`.
+    let file_name = match uri_path {
+        Some(_) => percent_decode(file_name),
+        None => file_name.to_string(),
+    };
+    let path = Path::new(&file_name);
     if let Some(stem) = path.file_stem().and_then(|value| value.to_str()) {
         return stem.to_string();
     }
     trimmed.to_string()
 }
 
-fn is_year_token(token: &str, current_year: i32) -> bool {
-    if token.len() != 4 {
-        return false;
+/// Decodes `%XX` escapes, leaving malformed ones as they are. Bytes that
+/// don't form valid UTF-8 are replaced rather than dropping the title.
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] == b'%' {
+            let hex = value.get(idx + 1..idx + 3);
+            if let Some(byte) = hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                decoded.push(byte);
+                idx += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[idx]);
+        idx += 1;
     }
-    let Ok(value) = token.parse::<i32>() else {
-        return false;
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Titles `http(s)://` MRLs from their last path segment, ignoring host and query.
+/// Returns `None` for anything that isn't a network URL.
+fn stream_title(raw: &str) -> Option<(String, bool)> {
+    let trimmed = raw.trim();
+    let lower = trimmed.to_ascii_lowercase();
+    let rest = if lower.starts_with("http://") {
+        &trimmed["http://".len()..]
+    } else if lower.starts_with("https://") {
+        &trimmed["https://".len()..]
+    } else {
+        return None;
     };
-    value >= 1900 && value <= current_year + 1
+
+    let rest = rest.split(['?', '#']).next().unwrap_or(rest);
+    let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let Some(segment) = path.split('/').rev().find(|segment| !segment.is_empty()) else {
+        return Some((host.to_string(), true));
+    };
+
+    let segment_path = Path::new(segment);
+    match segment_path.file_stem().and_then(|value| value.to_str()) {
+        Some(stem) if segment_path.extension().is_some() => Some((stem.to_string(), false)),
+        _ => Some((segment.to_string(), true)),
+    }
+}
+
+fn is_year_token(token: &str, latest_year: i32) -> bool {
+    year_value(token).is_some_and(|value| value >= 1900 && value <= latest_year)
+}
+
+fn year_value(token: &str) -> Option<i32> {
+    if token.len() != 4 || !token.chars().all(|ch| ch.is_ascii_digit()) {
+        return None;
+    }
+    token.parse().ok()
 }
 
 fn current_year() -> i32 {
@@ -166,17 +1228,105 @@ fn current_year() -> i32 {
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
         .as_secs() as i64;
-    let days = seconds / 86_400;
-    let z = days + 719_468;
-    let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
-    let doe = z - era * 146_097;
-    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
-    let y = yoe + era * 400;
-    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
-    let mp = (5 * doy + 2) / 153;
-    let m = mp + if mp < 10 { 3 } else { -9 };
-    let year = y + if m <= 2 { 1 } else { 0 };
-    year as i32
+    DateTime::from_unix(seconds).year as i32
+}
+
+/// A UTC calendar date and time, as read from a log timestamp. Displays as
+/// an ISO 8601 timestamp, `2025-01-01T20:00:00Z`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DateTime {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+}
+
+impl std::fmt::Display for DateTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+impl DateTime {
+    pub fn from_unix(seconds: i64) -> Self {
+        let days = seconds.div_euclid(86_400);
+        let secs_of_day = seconds.rem_euclid(86_400);
+        let z = days + 719_468;
+        let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
+        let doe = z - era * 146_097;
+        let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+        let y = yoe + era * 400;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let d = doy - (153 * mp + 2) / 5 + 1;
+        let m = mp + if mp < 10 { 3 } else { -9 };
+        Self {
+            year: y + if m <= 2 { 1 } else { 0 },
+            month: m as u32,
+            day: d as u32,
+            hour: (secs_of_day / 3_600) as u32,
+            minute: (secs_of_day % 3_600 / 60) as u32,
+            second: (secs_of_day % 60) as u32,
+        }
+    }
+
+    pub fn to_unix(&self) -> i64 {
+        let y = self.year - if self.month <= 2 { 1 } else { 0 };
+        let era = if y >= 0 { y } else { y - 399 } / 400;
+        let yoe = y - era * 400;
+        let mp = (i64::from(self.month) + 9) % 12;
+        let doy = (153 * mp + 2) / 5 + i64::from(self.day) - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        let days = era * 146_097 + doe - 719_468;
+        days * 86_400
+            + i64::from(self.hour) * 3_600
+            + i64::from(self.minute) * 60
+            + i64::from(self.second)
+    }
+}
+
+/// Parses the ISO 8601 shapes loggers write (`2025-01-01`, `2025-01-01T10:00:00Z`,
+/// `2025-01-01 10:00:00+01:00`, ...) and normalizes them to UTC. Timestamps
+/// without an offset are taken as UTC, like the VLC logger writes them.
+pub fn parse_timestamp(value: &str) -> Option<DateTime> {
+    let captures = cleaners().timestamp.captures(value.trim())?;
+    let number = |idx: usize| -> Option<u32> {
+        captures.get(idx).map_or(Some(0), |part| part.as_str().parse().ok())
+    };
+    let parsed = DateTime {
+        year: captures[1].parse().ok()?,
+        month: number(2)?,
+        day: number(3)?,
+        hour: number(4)?,
+        minute: number(5)?,
+        second: number(6)?,
+    };
+    if !(1..=12).contains(&parsed.month)
+        || !(1..=31).contains(&parsed.day)
+        || parsed.hour > 23
+        || parsed.minute > 59
+        || parsed.second > 60
+    {
+        return None;
+    }
+
+    let offset_seconds = match captures.get(7).map(|part| part.as_str()) {
+        None | Some("Z") => 0,
+        Some(offset) => {
+            let sign = if offset.starts_with('-') { -1 } else { 1 };
+            let digits: String = offset[1..].chars().filter(|ch| ch.is_ascii_digit()).collect();
+            let hours: i64 = digits.get(..2)?.parse().ok()?;
+            let minutes: i64 = digits.get(2..)?.parse().ok()?;
+            sign * (hours * 3_600 + minutes * 60)
+        }
+    };
+    Some(DateTime::from_unix(parsed.to_unix() - offset_seconds))
 }
 
 #[cfg(test)]
@@ -201,23 +1351,661 @@ mod tests {
         assert_eq!(cleaned, "Amores Perros");
     }
 
+    #[test]
+    fn cleans_corpus() {
+        for (input, expected) in CLEANER_CORPUS {
+            let cleaned = clean_title(input);
+            assert_eq!(cleaned, *expected, "cleaning {input}");
+            assert_eq!(title_warning(&cleaned), None, "cleaning {input}");
+        }
+    }
+
+    #[test]
+    fn warns_about_empty_and_tagged_titles() {
+        assert_eq!(title_warning("  "), Some("empty title"));
+        assert_eq!(title_warning("Dune x264"), Some("release tag survived cleaning"));
+        assert_eq!(title_warning("Dune"), None);
+    }
+
+    #[test]
+    fn strips_but_ignores_trailing_future_years() {
+        let soon = current_year() + 4;
+        let (title, year) = clean_title_and_year(&format!("Movie.{soon}.1080p.mkv"));
+        assert_eq!(title, "Movie");
+        assert_eq!(year, None);
+
+        let next = current_year() + 1;
+        assert_eq!(clean_title_and_year(&format!("Movie.{next}.mkv")), ("Movie".to_string(), Some(next)));
+    }
+
+    #[test]
+    fn year_policy_boundaries_follow_the_given_year() {
+        let clean = |raw: &str| clean_title_and_year_with_year(raw, 2025);
+        assert_eq!(clean("Movie.2026.mkv"), ("Movie".to_string(), Some(2026)));
+        assert_eq!(clean("Movie.2027.mkv"), ("Movie".to_string(), None));
+        assert_eq!(clean("Movie.2035.mkv"), ("Movie".to_string(), None));
+        assert_eq!(clean("Movie.2036.mkv"), ("Movie 2036".to_string(), None));
+        assert_eq!(clean_title_and_year_with_year("Movie.2027.mkv", 2026).1, Some(2027));
+
+        assert_eq!(clean("Movie.1900.mkv"), ("Movie".to_string(), Some(1900)));
+        assert_eq!(clean("Movie.1899.mkv"), ("Movie 1899".to_string(), None));
+    }
+
+    #[test]
+    fn wider_window_accepts_future_release_years() {
+        let cleaner = TitleCleaner::default().with_future_year_window(5);
+        let soon = current_year() + 4;
+        let (title, year) = cleaner.clean_with_year(&format!("Screener.Movie.{soon}.mkv"));
+        assert_eq!(title, "Screener Movie");
+        assert_eq!(year, Some(soon));
+    }
+
+    #[test]
+    fn keeps_years_that_are_the_title() {
+        let year_of = |raw: &str| clean_title_and_year(raw).1;
+        assert_eq!(clean_title_and_year("1917.2019.1080p.mkv"), ("1917".to_string(), Some(2019)));
+        assert_eq!(clean_title_and_year("2012.mkv"), ("2012".to_string(), None));
+        assert_eq!(clean_title_and_year("1917.mkv"), ("1917".to_string(), None));
+        assert_eq!(year_of("2001.A.Space.Odyssey.mkv"), None);
+        assert_eq!(year_of("2001: A Space Odyssey.mkv"), None);
+        assert_eq!(year_of("2001_A_Space_Odyssey_1968.mkv"), Some(1968));
+        assert_eq!(clean_title_and_year("1984.1984.mkv"), ("1984".to_string(), Some(1984)));
+        assert_eq!(clean_title_and_year("2012.2009.mkv"), ("2012".to_string(), Some(2009)));
+        assert_eq!(clean_title_and_year("1984.1984.1080p.mkv"), ("1984".to_string(), Some(1984)));
+    }
+
+    #[test]
+    fn composes_decomposed_accents() {
+        let (title, year) = clean_title_and_year("Ame\u{0301}lie.2001.1080p.mkv");
+        assert_eq!(title, "Am\u{e9}lie");
+        assert_eq!(year, Some(2001));
+        assert_eq!(ascii_fold(&title).as_deref(), Some("Amelie"));
+        assert_eq!(ascii_fold("Ame\u{0301}lie").as_deref(), Some("Amelie"));
+        let folded = ascii_fold("Sm\u{f8}rrebr\u{f8}d Stra\u{df}e");
+        assert_eq!(folded.as_deref(), Some("Smorrebrod Strasse"));
+        assert_eq!(ascii_fold("Dune"), None);
+        assert_eq!(ascii_fold("\u{5343}\u{3068}\u{5343}\u{5c0b}"), None);
+    }
+
+    #[test]
+    fn keeps_far_future_years_as_title_text() {
+        let far = current_year() + FUTURE_YEAR_HORIZON + 1;
+        assert_eq!(clean_title(&format!("Space.Odyssey.{far}.mkv")), format!("Space Odyssey {far}"));
+    }
+
     #[test]
     fn parses_pipe_delimited_log_lines() {
         let entry = parse_log_line("2025-01-01T10:00:00Z|C:\\Movies\\Blade.Runner.2049.1080p.mkv")
             .expect("entry");
         assert_eq!(entry.watched_at.as_deref(), Some("2025-01-01T10:00:00Z"));
         assert_eq!(entry.raw_title, "Blade.Runner.2049.1080p");
+        assert_eq!(entry.source_path.as_deref(), Some("C:\\Movies\\Blade.Runner.2049.1080p.mkv"));
         assert_eq!(entry.cleaned_title, "Blade Runner 2049");
     }
 
+    #[test]
+    fn parses_json_log_lines_alongside_delimited_ones() {
+        let json = concat!(
+            r#"{"path":"file:///films/Heat.1995.mkv","#,
+            r#""timestamp":"2025-01-01T20:00:00Z","position":0.4}"#
+        );
+        let entry = parse_log_line(json).expect("json entry");
+        assert_eq!(entry.watched_at.as_deref(), Some("2025-01-01T20:00:00Z"));
+        assert_eq!(entry.cleaned_title, "Heat");
+        assert_eq!(entry.release_year, Some(1995));
+        assert_eq!(entry.source_path.as_deref(), Some("/films/Heat.1995.mkv"));
+        assert_eq!((entry.position, entry.duration), (Some(0.4), None));
+
+        let unix = parse_log_line(r#"{"path":"/films/Alien.1979.mkv","timestamp":1735761600}"#)
+            .expect("unix timestamp");
+        assert_eq!(unix.watched_at.as_deref(), Some("2025-01-01T20:00:00Z"));
+        let untimed = parse_log_line(r#"  {"path":"/films/Alien.1979.mkv"}  "#).expect("untimed");
+        assert_eq!((untimed.watched_at, untimed.cleaned_title.as_str()), (None, "Alien"));
+        let timed = r#"{"path":"/films/Alien.1979.mkv","position":1.7,"duration":7020.4}"#;
+        let timed = parse_log_line(timed).expect("duration");
+        assert_eq!((timed.position, timed.duration), (None, Some(7020)));
+
+        let root = test_support::scratch_dir("json_lines_log");
+        let log = root.join("watch.txt");
+        let lines = [
+            r#"{"path":"/films/Heat.1995.mkv","timestamp":"2025-01-01T20:00:00Z"}"#,
+            "2025-01-02T20:00:00Z|/films/Dune.2021.mkv",
+        ];
+        std::fs::write(&log, lines.join("\n")).expect("write log");
+        let titles: Vec<String> = read_watch_log(&log)
+            .expect("read log")
+            .into_iter()
+            .map(|entry| entry.cleaned_title)
+            .collect();
+        assert_eq!(titles, vec!["Heat", "Dune"]);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
     #[test]
     fn parses_tab_delimited_log_lines() {
         let entry = parse_log_line("2025-01-01T10:00:00Z\tAlien.1979.720p.mkv")
             .expect("entry");
         assert_eq!(entry.watched_at.as_deref(), Some("2025-01-01T10:00:00Z"));
+        assert_eq!(entry.source_path.as_deref(), Some("Alien.1979.720p.mkv"));
         assert_eq!(entry.cleaned_title, "Alien");
     }
 
+    #[test]
+    fn splits_glued_resolution_tags() {
+        assert_eq!(clean_title("Arrival2160p.mkv"), "Arrival");
+        assert_eq!(clean_title("Dune.2021.1080pBluRay.x265.mkv"), "Dune");
+        assert_eq!(clean_title("Sicario.2015.720pWEBRip"), "Sicario");
+        assert_eq!(clean_title("Room.237.2012.mkv"), "Room 237");
+        assert_eq!(clean_title("Apollo.13.1995.mkv"), "Apollo 13");
+    }
+
+    #[test]
+    fn detects_release_groups() {
+        let scene = release_group("Heat.1995.1080p.BluRay.x264-SPARKS.mkv");
+        assert_eq!(scene.as_deref(), Some("SPARKS"));
+        assert_eq!(release_group(CLEANER_CORPUS[2].0).as_deref(), Some("YTS.MX"));
+        assert_eq!(release_group(CLEANER_CORPUS[7].0).as_deref(), Some("YTS.MX"));
+        assert_eq!(
+            release_group("[SubsPlease] Frieren - 01 (1080p).mkv").as_deref(),
+            Some("SubsPlease")
+        );
+        assert_eq!(release_group("Inception.2010.720p.YIFY.mp4").as_deref(), Some("YIFY"));
+        assert_eq!(release_group("Dune.2021.2160p.WEB-DL.mkv"), None);
+        assert_eq!(release_group("Spider-Man.2002.mkv"), None);
+    }
+
+    #[test]
+    fn scene_profile_drops_every_word_after_the_tags() {
+        let scene = CleanProfile::Scene.cleaner();
+        assert_eq!(scene.clean("The.Batman.2022.1080p.WEB-DL.x264-FGT.mkv"), "The Batman");
+        assert_eq!(scene.clean("Heat.1995.1080p.BluRay.Directors.Cut.mkv"), "Heat");
+        let (_, metadata) = scene.parse_release("Heat.1995.1080p.BluRay.Directors.Cut.mkv");
+        assert_eq!(metadata.release_group, None);
+        // Standard keeps ordinary words there in case they belong to the title.
+        let standard = clean_title("Heat.1995.1080p.BluRay.Directors.Cut.mkv");
+        assert_eq!(standard, "Heat Directors Cut");
+    }
+
+    #[test]
+    fn drops_release_groups_trailing_the_tags() {
+        let cases = [
+            ("The.Batman.2022.1080p.WEB-DL-GROUPNAME.mkv", "The Batman"),
+            ("Heat.1995.1080p.BluRay.x264-RARBG.mkv", "Heat"),
+            ("Heat.1995.1080p.BluRay.x264-YTS.mp4", "Heat"),
+            ("Heat.1995.1080p.BluRay.x264 [GROUP].mkv", "Heat"),
+            ("The.Thing.1982.1080p.BluRay.x264-Grym.mkv", "The Thing"),
+            ("Heat 1995 1080p BluRay FLUX.mkv", "Heat"),
+            ("Se7en.1995.REMASTERED.1080p.BluRay.x264-AMIABLE.mkv", "Se7en REMASTERED"),
+        ];
+        for (raw, expected) in cases {
+            assert_eq!(clean_title(raw), expected, "{raw}");
+        }
+        let (_, metadata) = parse_release("Heat 1995 1080p BluRay FLUX.mkv");
+        assert_eq!(metadata.release_group.as_deref(), Some("FLUX"));
+
+        // Ordinary words after the tags, and years, stay.
+        assert_eq!(clean_title("Heat.1995.1080p.Directors.Cut.mkv"), "Heat Directors Cut");
+        assert_eq!(clean_title_and_year("Heat.1080p.1995.mkv"), ("Heat".to_string(), Some(1995)));
+        assert_eq!(clean_title("1080p.ALIEN.mkv"), "ALIEN");
+    }
+
+    #[test]
+    fn strips_user_supplied_fluff() {
+        let raw = "Cidade.de.Deus.2002.Dublado.DD+.1080p.mkv";
+        assert_eq!(clean_title(raw), "Cidade de Deus Dublado DD+");
+        assert_eq!(clean_title_with_extra(raw, &["dublado", "DD+"]), "Cidade de Deus");
+
+        let cleaner = TitleCleaner::default().with_extra_fluff(["[HC]", "Dub"]);
+        assert_eq!(cleaner.clean("Heat.1995.[HC].mkv"), "Heat");
+        // Whole words only: "Dubliners" keeps its "Dub".
+        assert_eq!(cleaner.clean("The.Dubliners.1987.Dub.mkv"), "The Dubliners");
+        assert_eq!(cleaner.extra_fluff.words(), ["[HC]", "Dub"]);
+        assert_eq!(TitleCleaner::default().with_extra_fluff([" "]), TitleCleaner::default());
+    }
+
+    #[test]
+    fn skips_sample_and_trailer_files() {
+        assert!(parse_log_line("2025-01-01T20:00:00Z|/movies/something.sample.mkv").is_none());
+        assert!(parse_log_line("Movie.2021.sample.mkv").is_none());
+        assert!(parse_log_line("Movie.2021.1080p.TRAILER.mkv").is_none());
+        assert!(parse_log_line("Movie.2021.Trailer.1080p.mkv").is_none());
+
+        let legit = parse_log_line("Free.Sample.2012.1080p.mkv").expect("real title");
+        assert_eq!(legit.cleaned_title, "Free Sample");
+        assert!(parse_log_line("Trailer.Park.Boys.The.Movie.2006.mkv").is_some());
+        assert!(parse_log_line("Samples.2021.mkv").is_some());
+        assert!(parse_log_line("Movie.2021.Extended.Sample.mkv").is_none());
+        assert!(parse_log_line("The.Sample.mkv").is_some());
+        assert!(parse_log_line("Trailer.mkv").is_some());
+        assert!(parse_log_line("1917.Sample.mkv").is_some());
+
+        let keep_all = CleanProfile::Raw.cleaner();
+        assert!(parse_log_line_with("Movie.2021.sample.mkv", &keep_all).is_some());
+    }
+
+    #[test]
+    fn strips_site_prefixes() {
+        let cases = [
+            ("www.TamilBlasters.com - Movie.2021.1080p.WEB-DL.mkv", "Movie"),
+            ("[TGx]Movie.2021.1080p.mkv", "Movie"),
+            ("www.1TamilMV.cx-Movie.2021.720p.mkv", "Movie"),
+            ("YTS.com - Heat.1995.mkv", "Heat"),
+            // Domains in the title itself, and dashes without a domain, stay.
+            ("Dot.com.2007.mkv", "Dot com"),
+            ("Call.Me - By.Your.Name.2017.mkv", "Call Me By Your Name"),
+        ];
+        for (raw, expected) in cases {
+            assert_eq!(clean_title(raw), expected, "{raw}");
+        }
+        assert_eq!(CleanProfile::Raw.cleaner().clean("www.site.com - Heat"), "www site com Heat");
+    }
+
+    #[test]
+    fn falls_back_to_bracketed_titles() {
+        assert_eq!(clean_title("[SubsPlease] Frieren - 01 [1080p].mkv"), "Frieren 01");
+        assert_eq!(clean_title("[Group][Cowboy Bebop][1080p].mkv"), "Cowboy Bebop");
+        assert_eq!(clean_title("[Erai-raws][Mob Psycho 100][05][720p].mkv"), "Mob Psycho 100");
+        assert_eq!(clean_title("[YTS.MX][1080p].mp4"), "");
+
+        let cleaner = TitleCleaner {
+            bracket_title_fallback: false,
+            ..TitleCleaner::default()
+        };
+        assert_eq!(cleaner.clean("[Group][Cowboy Bebop][1080p].mkv"), "");
+    }
+
+    #[test]
+    fn plex_profile_keeps_hyphenated_titles() {
+        let plex = CleanProfile::Plex.cleaner();
+        assert_eq!(plex.clean("Spider-Man (2002).mkv"), "Spider-Man");
+        assert_eq!(
+            plex.clean_with_year("Ant-Man and the Wasp 2018 - 1080p.mkv"),
+            ("Ant-Man and the Wasp".to_string(), Some(2018))
+        );
+        assert_eq!(clean_title("Spider-Man (2002).mkv"), "Spider Man");
+    }
+
+    #[test]
+    fn raw_profile_only_touches_separators() {
+        let raw = CleanProfile::Raw.cleaner();
+        assert_eq!(
+            raw.clean_with_year("Heat.1995.1080p_BluRay.mkv"),
+            ("Heat 1995 1080p BluRay".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn profiles_round_trip_by_name() {
+        for profile in CleanProfile::ALL {
+            assert_eq!(CleanProfile::from_name(profile.name()), Some(profile));
+        }
+        assert_eq!(CleanProfile::from_name("PLEX"), Some(CleanProfile::Plex));
+        assert_eq!(CleanProfile::from_name("p2p"), None);
+        assert_eq!(TitleCleaner::default(), CleanProfile::Standard.cleaner());
+    }
+
+    #[test]
+    fn keeps_stripped_release_details() {
+        let (title, metadata) = parse_release("Heat.1995.1080p.BluRay.x265.DTS.5.1-SPARKS.mkv");
+        assert_eq!(title, "Heat");
+        assert_eq!(
+            metadata,
+            WatchMetadata {
+                resolution: Some("1080p".to_string()),
+                video_codec: Some("x265".to_string()),
+                audio_codec: Some("DTS".to_string()),
+                source: Some("BluRay".to_string()),
+                release_group: Some("SPARKS".to_string()),
+            }
+        );
+
+        let (title, metadata) = parse_release(CLEANER_CORPUS[7].0);
+        assert_eq!(title, "Parasite");
+        assert_eq!(metadata.resolution.as_deref(), Some("1080p"));
+        assert_eq!(metadata.source.as_deref(), Some("BluRay"));
+
+        let (_, metadata) = parse_release(CLEANER_CORPUS[2].0);
+        assert_eq!(metadata.audio_codec.as_deref(), Some("AAC"));
+    }
+
+    #[test]
+    fn parses_episode_markers() {
+        let episode = parse_episode("Doctor.Who.2005.S01E02.720p.HDTV.mkv").expect("episode");
+        assert_eq!(episode.series_title, "Doctor Who");
+        assert_eq!((episode.season, episode.episode), (1, 2));
+
+        let alt = parse_episode("The_Office_3x07_Branch_Wars").expect("alt form");
+        assert_eq!(alt.series_title, "The Office");
+        assert_eq!((alt.season, alt.episode), (3, 7));
+
+        assert!(parse_episode("Blade.Runner.2049.1080p.mkv").is_none());
+        assert!(parse_episode("S01E01.mkv").is_none());
+    }
+
+    #[test]
+    fn watch_entries_serialize_with_stable_field_names() {
+        let entry = parse_log_line("2025-01-01T20:00:00Z|The.Office.S03E05.mkv").expect("entry");
+        let json = serde_json::to_value(&entry).expect("serialize");
+        let mut fields: Vec<&str> = json
+            .as_object()
+            .expect("object")
+            .keys()
+            .map(String::as_str)
+            .collect();
+        fields.sort_unstable();
+        assert_eq!(
+            fields,
+            vec![
+                "cleaned_title",
+                "duration",
+                "episode",
+                "id_hint",
+                "is_stream",
+                "part",
+                "position",
+                "raw_title",
+                "release_year",
+                "source_path",
+                "watched_at"
+            ]
+        );
+        assert_eq!(json["episode"]["season"], 3);
+    }
+
+    #[test]
+    fn extracts_id_hints_and_strips_them_from_the_title() {
+        let tmdb = parse_log_line("/movies/The Thing (1982) {tmdb-1091}.mkv").expect("entry");
+        assert_eq!(tmdb.id_hint, Some(IdHint::Tmdb(1091)));
+        assert_eq!(tmdb.cleaned_title, "The Thing");
+
+        let imdb = parse_log_line("Crash.2004.{IMDB-tt0375679}.1080p.mkv").expect("entry");
+        assert_eq!(imdb.id_hint, Some(IdHint::Imdb("tt0375679".to_string())));
+        assert_eq!(imdb.cleaned_title, "Crash");
+
+        let raw = CleanProfile::Raw.cleaner();
+        let kept = parse_log_line_with("Crash {imdb-tt0375679}.mkv", &raw).expect("entry");
+        assert_eq!(kept.cleaned_title, "Crash");
+
+        assert_eq!(parse_id_hint("Heat {tmdb-abc}.mkv"), None);
+        assert_eq!(parse_id_hint("Heat {imdb-12345}.mkv"), None);
+    }
+
+    #[test]
+    fn dedups_only_adjacent_repeats_on_the_same_day() {
+        let entries: Vec<WatchEntry> = [
+            "2025-01-01T20:00:00Z|Dune.2021.mkv",
+            "2025-01-01T20:03:00Z|Dune.2021.1080p.mkv",
+            "2025-01-01T20:09:00Z|dune.2021.mkv",
+            "2025-01-01T22:00:00Z|Alien.1979.mkv",
+            "2025-01-01T23:00:00Z|Dune.2021.mkv",
+            "2025-01-02T20:00:00Z|Dune.2021.mkv",
+            "2025-01-02T21:00:00Z|The.Office.S03E05.mkv",
+            "2025-01-02T21:30:00Z|The.Office.S03E06.mkv",
+        ]
+        .into_iter()
+        .filter_map(parse_log_line)
+        .collect();
+
+        let deduped: Vec<(String, Option<String>)> = dedup_consecutive(entries)
+            .into_iter()
+            .map(|entry| (entry.cleaned_title, entry.watched_at))
+            .collect();
+        let expected = [
+            ("Dune", "2025-01-01T20:00:00Z"),
+            ("Alien", "2025-01-01T22:00:00Z"),
+            ("Dune", "2025-01-01T23:00:00Z"),
+            ("Dune", "2025-01-02T20:00:00Z"),
+            ("The Office", "2025-01-02T21:00:00Z"),
+            ("The Office", "2025-01-02T21:30:00Z"),
+        ];
+        let expected: Vec<(String, Option<String>)> = expected
+            .iter()
+            .map(|(title, at)| (title.to_string(), Some(at.to_string())))
+            .collect();
+        assert_eq!(deduped, expected);
+    }
+
+    #[test]
+    fn strips_disc_markers_but_not_title_parts() {
+        let parsed = |raw: &str| {
+            let entry = parse_log_line(raw).expect("entry");
+            (entry.cleaned_title, entry.release_year, entry.part)
+        };
+        assert_eq!(parsed("Heat.1995.CD1.avi"), ("Heat".to_string(), Some(1995), Some(1)));
+        assert_eq!(parsed("Heat.1995.cd2.avi"), ("Heat".to_string(), Some(1995), Some(2)));
+        assert_eq!(parsed("Heat 1995 Disc 2.avi"), ("Heat".to_string(), Some(1995), Some(2)));
+        assert_eq!(parsed("Heat_1995_CD2.avi"), ("Heat".to_string(), Some(1995), Some(2)));
+        assert_eq!(parsed("Heat.1995.Part.2.avi"), ("Heat".to_string(), Some(1995), Some(2)));
+        assert_eq!(parsed("Heat.1995.pt1.avi"), ("Heat".to_string(), Some(1995), Some(1)));
+
+        let hallows = "Harry.Potter.and.the.Deathly.Hallows.Part.1.2010.mkv";
+        assert_eq!(
+            parsed(hallows),
+            ("Harry Potter and the Deathly Hallows Part 1".to_string(), Some(2010), None)
+        );
+        let kill_bill = parsed("Kill.Bill.Vol.1.2003.mkv");
+        assert_eq!(kill_bill, ("Kill Bill Vol 1".to_string(), Some(2003), None));
+        assert_eq!(parse_part("CDX.Story.mkv"), None);
+    }
+
+    #[test]
+    fn merges_consecutive_parts_into_one_watch() {
+        let entries: Vec<WatchEntry> = [
+            "2025-01-01T20:00:00Z|Heat.1995.CD1.avi",
+            "2025-01-01T20:02:00Z|Heat.1995.CD1.avi",
+            "2025-01-01T21:10:00Z|Heat.1995.CD2.avi",
+            "2025-01-02T20:00:00Z|Alien.1979.CD2.avi",
+            "2025-01-02T21:00:00Z|Alien.1979.CD1.avi",
+            "2025-01-03T20:00:00Z|Dune.2021.mkv",
+        ]
+        .into_iter()
+        .filter_map(parse_log_line)
+        .collect();
+
+        let merged = merge_parts(dedup_consecutive(entries));
+        let watches: Vec<(&str, Option<&str>, Option<u32>)> = merged
+            .iter()
+            .map(|entry| (entry.cleaned_title.as_str(), entry.watched_at.as_deref(), entry.part))
+            .collect();
+        assert_eq!(
+            watches,
+            vec![
+                ("Heat", Some("2025-01-01T20:00:00Z"), None),
+                ("Alien", Some("2025-01-02T20:00:00Z"), Some(2)),
+                ("Alien", Some("2025-01-02T21:00:00Z"), Some(1)),
+                ("Dune", Some("2025-01-03T20:00:00Z"), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn merges_reopens_within_the_window_only() {
+        let entries: Vec<WatchEntry> = [
+            "2025-01-01T20:00:00Z|Dune.2021.mkv",
+            "2025-01-01T20:00:05Z|Dune.2021.mkv",
+            "2025-01-01T20:00:32Z|Dune.2021.mkv",
+            "2025-01-01T20:01:00Z|Alien.1979.mkv",
+            "2025-01-04T20:00:00Z|Dune.2021.mkv",
+            "Dune.2021.mkv",
+            "2025-01-04T21:00:00Z|The.Office.S03E05.mkv",
+            "2025-01-04T21:00:10Z|The.Office.S03E06.mkv",
+        ]
+        .into_iter()
+        .filter_map(parse_log_line)
+        .collect();
+
+        let merged = merge_within(entries, Duration::from_secs(30));
+        let watches: Vec<(&str, Option<&str>)> = merged
+            .iter()
+            .map(|entry| (entry.cleaned_title.as_str(), entry.watched_at.as_deref()))
+            .collect();
+        assert_eq!(
+            watches,
+            vec![
+                ("Dune", Some("2025-01-01T20:00:00Z")),
+                ("Alien", Some("2025-01-01T20:01:00Z")),
+                ("Dune", Some("2025-01-04T20:00:00Z")),
+                ("Dune", None),
+                ("The Office", Some("2025-01-04T21:00:00Z")),
+                ("The Office", Some("2025-01-04T21:00:10Z")),
+            ]
+        );
+    }
+
+    #[test]
+    fn decodes_percent_encoded_file_uris() {
+        let spaced = "2025-01-01T20:00:00Z|file:///C:/Movies/The%20Matrix%20(1999).mkv";
+        let spaced = parse_log_line(spaced).expect("entry");
+        assert_eq!(spaced.raw_title, "The Matrix (1999)");
+        assert_eq!(spaced.cleaned_title, "The Matrix");
+        assert_eq!(spaced.source_path.as_deref(), Some("C:/Movies/The Matrix (1999).mkv"));
+
+        let bracketed = parse_log_line("file://nas/share/Heat%20%5B1995%5D%20%281080p%29.mkv")
+            .expect("entry");
+        assert_eq!(bracketed.raw_title, "Heat [1995] (1080p)");
+        assert_eq!(bracketed.cleaned_title, "Heat");
+        let unc = bracketed.source_path.as_deref();
+        assert_eq!(unc, Some("//nas/share/Heat [1995] (1080p).mkv"));
+
+        assert_eq!(extract_title("file:///films/Am%C3%A9lie%2.mkv"), "Amélie%2");
+        assert_eq!(extract_title("/films/100%20Percent.mkv"), "100%20Percent");
+    }
+
+    #[test]
+    fn episode_entries_keep_only_the_series_name() {
+        let entry = parse_log_line("2025-01-01T20:00:00Z|file:///tv/The.Office.S03E05.720p.mkv")
+            .expect("entry");
+        assert_eq!(entry.cleaned_title, "The Office");
+        let episode = entry.episode.expect("episode");
+        assert_eq!((episode.season, episode.episode), (3, 5));
+
+        let movie = parse_log_line("2025-01-01T20:00:00Z|Dune.2021.1080p.mkv").expect("entry");
+        assert!(movie.episode.is_none());
+    }
+
+    #[test]
+    fn parses_timestamps_to_utc() {
+        let utc = parse_timestamp("2025-03-01T00:30:00+01:00").expect("offset");
+        assert_eq!((utc.year, utc.month, utc.day, utc.hour, utc.minute), (2025, 2, 28, 23, 30));
+
+        let date_only = parse_timestamp("2024-02-29").expect("date");
+        assert_eq!((date_only.month, date_only.day, date_only.hour), (2, 29, 0));
+
+        let zulu = parse_timestamp("2025-01-01T10:00:00.250Z").expect("zulu");
+        assert_eq!(zulu.to_unix(), 1_735_725_600);
+        assert_eq!(DateTime::from_unix(zulu.to_unix()), zulu);
+
+        assert!(parse_timestamp("2025-13-01").is_none());
+        assert!(parse_timestamp("yesterday").is_none());
+    }
+
+    #[test]
+    fn parses_swapped_path_and_timestamp() {
+        let entry = parse_log_line("C:\\Movies\\Alien.1979.720p.mkv | 2025-01-01T10:00:00Z")
+            .expect("entry");
+        assert_eq!(entry.watched_at.as_deref(), Some("2025-01-01T10:00:00Z"));
+        assert_eq!(entry.cleaned_title, "Alien");
+        assert_eq!(entry.release_year, Some(1979));
+
+        let entry = parse_log_line("/movies/Dune.2021.mkv\t2025-01-01 10:00:00+01:00").expect("entry");
+        assert_eq!(entry.watched_at.as_deref(), Some("2025-01-01 10:00:00+01:00"));
+        assert_eq!(entry.cleaned_title, "Dune");
+    }
+
+    #[test]
+    fn titles_http_mrl_from_last_segment() {
+        let entry = parse_log_line(
+            "2025-01-01T10:00:00Z|https://example.com/media/Blade.Runner.2049.1080p.mkv?token=abc#t=10",
+        )
+        .expect("entry");
+        assert_eq!(entry.raw_title, "Blade.Runner.2049.1080p");
+        assert_eq!(entry.cleaned_title, "Blade Runner 2049");
+        assert!(!entry.is_stream);
+    }
+
+    #[test]
+    fn flags_http_mrl_without_filename_as_stream() {
+        let entry = parse_log_line("2025-01-01T10:00:00Z|http://host:8080/live/stream/").expect("entry");
+        assert_eq!(entry.raw_title, "stream");
+        assert!(entry.is_stream);
+
+        let entry = parse_log_line("2025-01-01T10:00:00Z|http://10.0.0.5:8080?channel=4").expect("entry");
+        assert_eq!(entry.raw_title, "10.0.0.5:8080");
+        assert!(entry.is_stream);
+    }
+
+    #[test]
+    fn streams_the_log_entry_by_entry() {
+        let root = test_support::scratch_dir("streaming-log");
+        let log_path = root.join("log.txt");
+        std::fs::write(
+            &log_path,
+            "2025-01-01T20:00:00Z|/movies/Dune.2021.1080p.mkv\r\n\n  \n\
+             2025-01-02T20:00:00Z|/movies/Alien.1979.mkv",
+        )
+        .expect("write log");
+
+        let mut streamed = Vec::new();
+        read_watch_log_streaming(&log_path, |entry| streamed.push(entry)).expect("stream");
+        let titles: Vec<&str> = streamed.iter().map(|entry| entry.cleaned_title.as_str()).collect();
+        assert_eq!(titles, vec!["Dune", "Alien"]);
+        assert_eq!(streamed, read_watch_log(&log_path).expect("read"));
+
+        let mut calls = 0;
+        read_watch_log_streaming(&root.join("missing.txt"), |_| calls += 1).expect("missing");
+        assert_eq!(calls, 0);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn reads_last_non_blank_line_across_chunks() {
+        let mut log = String::new();
+        for day in 0..400 {
+            log.push_str(&format!("2025-01-01T10:{:02}:00Z|C:\\Movies\\Filler.{day}.mkv\n", day % 60));
+        }
+        log.push_str("2025-02-01T21:00:00Z|C:\\Movies\\Dune.2021.1080p.mkv\n\n  \n");
+        assert!(log.len() > 8192);
+
+        let entry = last_parsed_line(&mut std::io::Cursor::new(log.into_bytes()), parse_log_line)
+            .expect("read")
+            .expect("entry");
+        assert_eq!(entry.watched_at.as_deref(), Some("2025-02-01T21:00:00Z"));
+        assert_eq!(entry.cleaned_title, "Dune");
+        assert_eq!(entry.release_year, Some(2021));
+    }
+
+    #[test]
+    fn last_line_of_single_line_or_blank_input() {
+        let non_blank = |line: &str| Some(line.trim().to_string()).filter(|line| !line.is_empty());
+        let mut single = std::io::Cursor::new(b"only line".to_vec());
+        assert_eq!(
+            last_parsed_line(&mut single, non_blank).expect("read").as_deref(),
+            Some("only line")
+        );
+
+        let mut blank = std::io::Cursor::new(b"\n \n".to_vec());
+        assert_eq!(last_parsed_line(&mut blank, non_blank).expect("read"), None);
+    }
+
+    #[test]
+    fn last_entry_skips_trailing_notes_and_samples() {
+        let root = test_support::scratch_dir("last_entry_notes");
+        let log = root.join("watch.log");
+        let mut text = String::from("2025-02-01T21:00:00Z|C:\\Movies\\Dune.2021.1080p.mkv\n");
+        text.push_str("2025-02-01T21:05:00Z|C:\\Movies\\Dune.2021.Sample.mkv\n");
+        // A note longer than one chunk, so the walk has to cross a boundary.
+        text.push_str(&format!("# {}\n\n", "rewatch soon ".repeat(400)));
+        std::fs::write(&log, text).expect("write log");
+
+        let entry = read_last_entry(&log).expect("read").expect("entry");
+        assert_eq!(entry.cleaned_title, "Dune");
+        assert_eq!(entry.watched_at.as_deref(), Some("2025-02-01T21:00:00Z"));
+
+        std::fs::write(&log, "# nothing watched yet\n").expect("write log");
+        assert_eq!(read_last_entry(&log).expect("read"), None);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
     #[test]
     fn ignores_blank_lines() {
         assert!(parse_log_line("   ").is_none());
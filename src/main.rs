@@ -1,29 +1,63 @@
 use std::env;
 use std::path::PathBuf;
 
-const USAGE: &str = "Usage:\n  goo [log-path]\n  goo enrich [log-path] [cache-path]";
+const USAGE: &str = "Usage:\n  goo clean [--profile=NAME] [--format ndjson] [--sort title|date] [--reverse] [log-path] [cache-path]\n  goo enrich [--refine] [--ics] [--include-raw] [--verbose] [--in-memory] [--jobs=N] [--profile=NAME] [--format ndjson] [log-path] [cache-path]\n  goo stats [--json] [log-path]\n  goo stats --genre-time [log-path] [cache-path]\n  goo stats --directors [log-path] [cache-path]\n  goo stats --collections [log-path] [cache-path]\n  goo stats --groups [log-path]\n  goo export letterboxd [log-path] [cache-path]\n  goo import trakt <export.json> [log-path]\n  goo cache repair [cache-path]\n  goo cache info [cache-path]\n  goo last [log-path]\n  goo titles [log-path]\n  goo report [log-path]\n  goo report --enrich|--html [log-path] [cache-path]\n  goo self-test\n  goo doctor [log-path] [cache-path]";
 
 fn main() {
     let mut args = env::args().skip(1);
     let first = args.next();
 
     match first.as_deref() {
+        Some("clean") => run_clean(args),
         Some("enrich") => run_enrich(args),
-        Some(path) => run_clean(Some(path.to_string())),
-        None => run_clean(None),
+        Some("stats") => run_stats(args),
+        Some("cache") => run_cache(args),
+        Some("export") => run_export(args),
+        Some("import") => run_import(args),
+        Some("last") => run_last(args.next()),
+        Some("titles") => run_titles(args.next()),
+        Some("report") => run_report(args),
+        Some("self-test") => run_self_test(),
+        Some("doctor") => run_doctor(args),
+        // The bare form predates `goo clean` and still works the same.
+        _ => run_clean(first.into_iter().chain(args)),
     }
 }
 
-fn run_clean(path: Option<String>) {
-    let log_path = match resolve_log_path(path) {
-        Some(path) => path,
-        None => {
-            eprintln!("{USAGE}\nLog path not found. Set GOO_LOG_PATH or pass a path.");
+fn run_clean(args: impl Iterator<Item = String>) {
+    let parsed = take_format(args).and_then(|(format, args)| {
+        take_sort(args.into_iter()).map(|(order, args)| (format, order, args))
+    });
+    let (format, order, args) = match parsed {
+        Ok(parsed) => parsed,
+        Err(message) => {
+            eprintln!("{USAGE}\n{message}");
             return;
         }
     };
+    let (positional, flags) = split_flags(args.into_iter());
+    let reverse = flags.iter().any(|flag| flag == "--reverse");
+    let profile = match profile_flag(&flags) {
+        Ok(profile) => profile,
+        Err(message) => {
+            eprintln!("{USAGE}\n{message}");
+            return;
+        }
+    };
+    let mut positional = positional.into_iter();
+    let log_path = resolve_log_path(positional.next());
+    // Accepted so `goo <log> <cache>` lines up with `goo enrich`, but cleaning
+    // never reads the cache.
+    if let Some(cache_path) = positional.next() {
+        eprintln!("Warning: ignoring cache path {cache_path}; only `goo enrich` uses the cache");
+    }
 
-    match goo::read_watch_log(&log_path) {
+    let entries = goo::read_watch_log_with(&log_path, &profile.cleaner()).map(|mut entries| {
+        goo::app::sort_entries(&mut entries, order, reverse);
+        entries
+    });
+    match entries {
+        Ok(entries) if format == OutputFormat::Ndjson => print_ndjson(&entries),
         Ok(entries) => {
             for entry in entries {
                 if let Some(watched_at) = entry.watched_at.as_deref() {
@@ -39,39 +73,528 @@ fn run_clean(path: Option<String>) {
     }
 }
 
-fn run_enrich(mut args: impl Iterator<Item = String>) {
+fn run_enrich(args: impl Iterator<Item = String>) {
+    let (format, args) = match take_format(args) {
+        Ok(parsed) => parsed,
+        Err(message) => {
+            eprintln!("{USAGE}\n{message}");
+            return;
+        }
+    };
+    let (positional, flags) = split_flags(args.into_iter());
+    let mut positional = positional.into_iter();
+    let log_path = resolve_log_path(positional.next());
+    let cache_path = positional.next().map(PathBuf::from);
+    let profile = match profile_flag(&flags) {
+        Ok(profile) => profile,
+        Err(message) => {
+            eprintln!("{USAGE}\n{message}");
+            return;
+        }
+    };
+    let concurrency = match jobs_flag(&flags) {
+        Ok(concurrency) => concurrency,
+        Err(message) => {
+            eprintln!("{USAGE}\n{message}");
+            return;
+        }
+    };
+
+    let request = goo::app::HistoryRequest {
+        cache_path: cache_path.as_deref(),
+        profile,
+        include_raw: flags.iter().any(|flag| flag == "--include-raw"),
+        verbose: flags.iter().any(|flag| flag == "--verbose"),
+        // Without --in-memory, stop before spending lookups that can't be saved.
+        on_unwritable_cache: if flags.iter().any(|flag| flag == "--in-memory") {
+            goo::app::UnwritableCache::InMemory
+        } else {
+            goo::app::UnwritableCache::Fail
+        },
+        concurrency,
+        ..goo::app::HistoryRequest::new(&log_path)
+    };
+    let result = if flags.iter().any(|flag| flag == "--refine") {
+        goo::app::refined_history(request, goo::enrich::DEFAULT_REFINE_THRESHOLD)
+    } else {
+        goo::app::enriched_history(request)
+    };
+    let Some(history) = report_history(result) else {
+        return;
+    };
+
+    if flags.iter().any(|flag| flag == "--ics") {
+        print!("{}", goo::export::to_ics(&history.entries));
+        return;
+    }
+    if format == OutputFormat::Ndjson {
+        print_ndjson(&history.entries);
+        return;
+    }
+    match serde_json::to_string(&history.entries) {
+        Ok(payload) => println!("{payload}"),
+        Err(error) => eprintln!("Failed to serialize output: {error}"),
+    }
+}
+
+fn run_stats(args: impl Iterator<Item = String>) {
+    let (positional, flags) = split_flags(args);
+    if flags.iter().any(|flag| flag == "--groups") {
+        run_release_groups(positional.into_iter().next());
+        return;
+    }
+    let genre_time = flags.iter().any(|flag| flag == "--genre-time");
+    let directors = flags.iter().any(|flag| flag == "--directors");
+    let collections = flags.iter().any(|flag| flag == "--collections");
+    if !genre_time && !directors && !collections {
+        let json = flags.iter().any(|flag| flag == "--json");
+        run_summary(positional.into_iter().next(), json);
+        return;
+    }
+
+    let mut positional = positional.into_iter();
+    let log_path = resolve_log_path(positional.next());
+    let cache_path = positional.next().map(PathBuf::from);
+
+    if directors {
+        let result = goo::app::load_credited_history(&log_path, cache_path.as_deref(), None);
+        let Some(history) = report_history(result) else {
+            return;
+        };
+        for (director, count) in goo::stats::top_directors(&history.entries) {
+            println!("{director}\t{count}");
+        }
+        return;
+    }
+
+    let result = goo::app::load_detailed_history(&log_path, cache_path.as_deref(), None);
+    let Some(history) = report_history(result) else {
+        return;
+    };
+
+    if collections {
+        for group in goo::enrich::group_by_collection(&history.entries) {
+            println!("{}\t{}", group.name, group.films);
+        }
+        return;
+    }
+    for (genre, time) in goo::stats::genre_watch_time(&history.entries) {
+        println!("{genre}\t{:.1}h", time.as_secs_f64() / 3600.0);
+    }
+}
+
+fn run_summary(path: Option<String>, json: bool) {
+    let log_path = resolve_log_path(path);
+    // A missing log reads as empty, which summarizes to zeros.
+    let entries = match goo::read_watch_log(&log_path) {
+        Ok(entries) => goo::merge_parts(entries),
+        Err(error) => {
+            eprintln!("Failed to read log: {error}");
+            return;
+        }
+    };
+    let summary = goo::stats::compute(&entries).summary(goo::stats::DEFAULT_TOP_TITLES);
+
+    if json {
+        match serde_json::to_string_pretty(&summary) {
+            Ok(payload) => println!("{payload}"),
+            Err(error) => eprintln!("Failed to serialize output: {error}"),
+        }
+        return;
+    }
+    println!("Total entries: {}", summary.total_entries);
+    println!("Unique titles: {}", summary.unique_titles);
+    if !summary.decades.is_empty() {
+        println!("\nBy decade:");
+        for (decade, count) in &summary.decades {
+            println!("  {decade}s\t{count}");
+        }
+    }
+    if !summary.most_watched.is_empty() {
+        println!("\nMost watched:");
+        for title in &summary.most_watched {
+            match title.release_year {
+                Some(year) => println!("  {} ({year})\t{}", title.title, title.watches),
+                None => println!("  {}\t{}", title.title, title.watches),
+            }
+        }
+    }
+}
+
+fn run_release_groups(path: Option<String>) {
+    let log_path = resolve_log_path(path);
+
+    match goo::read_watch_log(&log_path) {
+        Ok(entries) => {
+            for (group, count) in goo::stats::release_groups(&entries) {
+                println!("{group}\t{count}");
+            }
+        }
+        Err(error) => eprintln!("Failed to read log: {error}"),
+    }
+}
+
+fn run_export(mut args: impl Iterator<Item = String>) {
+    if args.next().as_deref() != Some("letterboxd") {
+        eprintln!("{USAGE}");
+        return;
+    }
+    let log_path = resolve_log_path(args.next());
+    let cache_path = args.next().map(PathBuf::from);
+
+    let result = goo::app::load_enriched_history(&log_path, cache_path.as_deref(), None);
+    let Some(history) = report_history(result) else {
+        return;
+    };
+    print!("{}", goo::export::to_letterboxd_csv(&history.entries));
+}
+
+fn run_import(mut args: impl Iterator<Item = String>) {
+    let (Some("trakt"), Some(export_path)) = (args.next().as_deref(), args.next()) else {
+        eprintln!("{USAGE}");
+        return;
+    };
     let log_path = resolve_log_path(args.next());
-    let Some(log_path) = log_path else {
-        eprintln!("{USAGE}\nLog path not found. Set GOO_LOG_PATH or pass a path.");
+
+    match goo::app::import_trakt(&PathBuf::from(export_path), &log_path) {
+        Ok(import) => {
+            println!("Added {} watches to {}", import.lines.len(), log_path.display());
+            if import.skipped > 0 {
+                println!("Skipped {} items that are not a movie or an episode", import.skipped);
+            }
+        }
+        Err(error) => eprintln!("Import failed: {error}"),
+    }
+}
+
+fn run_cache(mut args: impl Iterator<Item = String>) {
+    let command = args.next();
+    if !matches!(command.as_deref(), Some("repair" | "info")) {
+        eprintln!("{USAGE}");
         return;
+    }
+    let cache_path = match args.next() {
+        Some(path) => PathBuf::from(path),
+        None => goo::app::resolve_cache_path(None, &resolve_log_path(None)),
     };
+    if command.as_deref() == Some("info") {
+        run_cache_info(&cache_path);
+        return;
+    }
+
+    let (cache, repair) = match goo::enrich::MovieCache::load_lenient(&cache_path) {
+        Ok(loaded) => loaded,
+        Err(error) => {
+            eprintln!("Failed to read cache: {error}");
+            return;
+        }
+    };
+    let backup = cache_path.with_extension("json.bak");
+    if let Err(error) = std::fs::copy(&cache_path, &backup) {
+        eprintln!("Failed to back up cache to {}: {error}", backup.display());
+        return;
+    }
+    if let Err(error) = cache.save(&cache_path) {
+        eprintln!("Failed to write cache: {error}");
+        return;
+    }
+    println!(
+        "Recovered {} entries, dropped {}. Original kept at {}",
+        repair.recovered,
+        repair.dropped,
+        backup.display()
+    );
+}
+
+/// Prints how much the cache holds and how old its lookups are.
+fn run_cache_info(cache_path: &std::path::Path) {
+    let size = match std::fs::metadata(cache_path) {
+        Ok(meta) => meta.len(),
+        Err(error) => {
+            eprintln!("Failed to read cache {}: {error}", cache_path.display());
+            return;
+        }
+    };
+    let (cache, warning) = goo::enrich::MovieCache::load_checked(cache_path);
+    if let Some(warning) = warning {
+        eprintln!("Cache warning: {warning}");
+    }
+    let info = cache.info();
+    println!("Cache: {} ({size} bytes)", cache_path.display());
+    println!("Films: {}", info.movies);
+    println!("Series: {}", info.shows);
+    if let Some((key, age)) = &info.oldest {
+        println!("Oldest: {key} ({} ago)", format_age(*age));
+    }
+    if let Some((key, age)) = &info.newest {
+        println!("Newest: {key} ({} ago)", format_age(*age));
+    }
+    if info.undated > 0 {
+        println!("Undated: {}", info.undated);
+    }
+}
+
+/// The largest whole unit of `age`: `3d`, `5h`, `12m` or `40s`.
+fn format_age(age: std::time::Duration) -> String {
+    let secs = age.as_secs();
+    match secs {
+        86_400.. => format!("{}d", secs / 86_400),
+        3_600.. => format!("{}h", secs / 3_600),
+        60.. => format!("{}m", secs / 60),
+        _ => format!("{secs}s"),
+    }
+}
+
+fn run_titles(path: Option<String>) {
+    let log_path = resolve_log_path(path);
+
+    match goo::read_watch_log(&log_path) {
+        Ok(entries) => {
+            for (title, year) in goo::export::title_year_list(&entries) {
+                match year {
+                    Some(year) => println!("{title} ({year})"),
+                    None => println!("{title}"),
+                }
+            }
+        }
+        Err(error) => eprintln!("Failed to read log: {error}"),
+    }
+}
+
+fn run_report(args: impl Iterator<Item = String>) {
+    let (positional, flags) = split_flags(args);
+    let html = flags.iter().any(|flag| flag == "--html");
+    let enrich = html || flags.iter().any(|flag| flag == "--enrich");
+    let mut args = positional.into_iter();
+    let log_path = resolve_log_path(args.next());
+    if !enrich {
+        run_month_report(&log_path);
+        return;
+    }
+    let cache_path = args.next().map(PathBuf::from);
+
+    let result = goo::app::load_enriched_history(&log_path, cache_path.as_deref(), None);
+    let Some(history) = report_history(result) else {
+        return;
+    };
+    if html {
+        print!("{}", goo::export::to_html(&history.entries));
+    } else {
+        print!("{}", goo::export::to_markdown(&history.entries));
+    }
+}
+
+/// Lists what was watched each month straight from the log, without TMDB.
+fn run_month_report(log_path: &std::path::Path) {
+    let entries = match goo::read_watch_log(log_path) {
+        Ok(entries) => entries,
+        Err(error) => {
+            eprintln!("Failed to read log: {error}");
+            return;
+        }
+    };
+    for (idx, (month, watched)) in goo::app::group_by_month(entries).into_iter().enumerate() {
+        if idx > 0 {
+            println!();
+        }
+        println!("{month} ({} watched)", watched.len());
+        for entry in watched {
+            match (&entry.episode, entry.release_year) {
+                (Some(episode), _) => println!(
+                    "  {} S{:02}E{:02}",
+                    episode.series_title, episode.season, episode.episode
+                ),
+                (None, Some(year)) => println!("  {} ({year})", entry.cleaned_title),
+                (None, None) => println!("  {}", entry.cleaned_title),
+            }
+        }
+    }
+}
+
+fn run_last(path: Option<String>) {
+    let log_path = resolve_log_path(path);
+
+    match goo::read_last_entry(&log_path) {
+        Ok(Some(entry)) => match entry.release_year {
+            Some(year) => println!("{} ({year})", entry.cleaned_title),
+            None => println!("{}", entry.cleaned_title),
+        },
+        Ok(None) => {}
+        Err(error) => eprintln!("Failed to read log: {error}"),
+    }
+}
+
+fn run_self_test() {
+    let mut flagged = 0;
+    for (input, expected) in goo::CLEANER_CORPUS {
+        let cleaned = goo::clean_title(input);
+        let problem = match goo::title_warning(&cleaned) {
+            Some(warning) => Some(warning.to_string()),
+            None if cleaned != *expected => Some(format!("expected \"{expected}\"")),
+            None => None,
+        };
+        match problem {
+            Some(problem) => {
+                flagged += 1;
+                println!("FLAG  {input} -> \"{cleaned}\" ({problem})");
+            }
+            None => println!("ok    {input} -> \"{cleaned}\""),
+        }
+    }
+    println!("{flagged} of {} cases flagged", goo::CLEANER_CORPUS.len());
+}
+
+/// Prints a pass/fail checklist of the setup, exiting nonzero if anything
+/// enrichment needs is broken.
+fn run_doctor(mut args: impl Iterator<Item = String>) {
+    let log_path = resolve_log_path(args.next());
     let cache_path = args.next().map(PathBuf::from);
 
-    let history = match goo::app::load_enriched_history(&log_path, cache_path.as_deref(), None) {
+    let checks = goo::app::diagnose(&log_path, cache_path.as_deref());
+    for check in &checks {
+        let status = match (check.passed, check.critical) {
+            (true, _) => "ok  ",
+            (false, false) => "WARN",
+            (false, true) => "FAIL",
+        };
+        println!("{status}  {}: {}", check.name, check.detail);
+        if let (false, Some(hint)) = (check.passed, check.hint) {
+            println!("      {hint}");
+        }
+    }
+    if checks.iter().any(|check| !check.passed && check.critical) {
+        std::process::exit(1);
+    }
+}
+
+/// Prints enrichment failures and cache warnings, returning the history on success.
+fn report_history(
+    result: Result<goo::app::EnrichedHistory, goo::app::AppError>,
+) -> Option<goo::app::EnrichedHistory> {
+    let history = match result {
         Ok(history) => history,
         Err(goo::app::AppError::Tmdb(error)) => {
             eprintln!("TMDB error: {error}. Set TMDB_API_KEY to continue.");
-            return;
+            return None;
         }
         Err(error) => {
             eprintln!("Failed to enrich log: {error}");
-            return;
+            return None;
         }
     };
 
-    if let Some(warning) = history.cache_warning.as_deref() {
+    if let Some(warning) = history.cache_status.warning() {
         eprintln!("Cache warning: {warning}");
     }
+    if let Some(warning) = history.overrides_warning.as_deref() {
+        eprintln!("Warning: {warning}");
+    }
+    Some(history)
+}
 
-    match serde_json::to_string(&history.entries) {
-        Ok(payload) => println!("{payload}"),
-        Err(error) => eprintln!("Failed to serialize output: {error}"),
+/// How a command prints its entries, chosen with `--format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    /// Tab-separated lines for `goo`, one JSON array for `goo enrich`.
+    Default,
+    /// One JSON object per entry per line.
+    Ndjson,
+}
+
+/// Pulls `--format VALUE` (or `--format=VALUE`) out of `args` before the rest
+/// is split, so the value isn't mistaken for the log path.
+fn take_format(
+    args: impl Iterator<Item = String>,
+) -> Result<(OutputFormat, Vec<String>), String> {
+    let mut format = OutputFormat::Default;
+    let mut rest = Vec::new();
+    let mut args = args;
+    while let Some(arg) = args.next() {
+        let value = match arg.strip_prefix("--format") {
+            Some("") => args.next().unwrap_or_default(),
+            Some(value) if value.starts_with('=') => value[1..].to_string(),
+            _ => {
+                rest.push(arg);
+                continue;
+            }
+        };
+        format = match value.as_str() {
+            "ndjson" => OutputFormat::Ndjson,
+            "default" => OutputFormat::Default,
+            _ => {
+                return Err(format!("Unknown format \"{value}\". Expected one of: default, ndjson"))
+            }
+        };
+    }
+    Ok((format, rest))
+}
+
+/// Pulls `--sort VALUE` (or `--sort=VALUE`) out of `args`, like [`take_format`].
+fn take_sort(
+    args: impl Iterator<Item = String>,
+) -> Result<(goo::app::EntryOrder, Vec<String>), String> {
+    let mut order = goo::app::EntryOrder::default();
+    let mut rest = Vec::new();
+    let mut args = args;
+    while let Some(arg) = args.next() {
+        let value = match arg.strip_prefix("--sort") {
+            Some("") => args.next().unwrap_or_default(),
+            Some(value) if value.starts_with('=') => value[1..].to_string(),
+            _ => {
+                rest.push(arg);
+                continue;
+            }
+        };
+        order = goo::app::EntryOrder::from_name(&value).ok_or_else(|| {
+            format!("Unknown sort \"{value}\". Expected one of: file, title, date")
+        })?;
+    }
+    Ok((order, rest))
+}
+
+fn print_ndjson<T: serde::Serialize>(items: &[T]) {
+    for item in items {
+        match serde_json::to_string(item) {
+            Ok(line) => println!("{line}"),
+            Err(error) => {
+                eprintln!("Failed to serialize output: {error}");
+                return;
+            }
+        }
+    }
+}
+
+/// Separates `--flag` arguments from positional ones, keeping both in order.
+fn split_flags(args: impl Iterator<Item = String>) -> (Vec<String>, Vec<String>) {
+    args.partition(|arg| !arg.starts_with("--"))
+}
+
+/// Reads `--profile=NAME`, defaulting to the standard cleaner.
+fn profile_flag(flags: &[String]) -> Result<goo::CleanProfile, String> {
+    let Some(name) = flags.iter().find_map(|flag| flag.strip_prefix("--profile=")) else {
+        return Ok(goo::CleanProfile::default());
+    };
+    goo::CleanProfile::from_name(name).ok_or_else(|| {
+        let names: Vec<&str> = goo::CleanProfile::ALL.iter().map(|profile| profile.name()).collect();
+        format!("Unknown profile \"{name}\". Expected one of: {}", names.join(", "))
+    })
+}
+
+/// Reads `--jobs=N`, how many lookups run at once; one at a time by default.
+fn jobs_flag(flags: &[String]) -> Result<usize, String> {
+    let Some(value) = flags.iter().find_map(|flag| flag.strip_prefix("--jobs=")) else {
+        return Ok(1);
+    };
+    match value.parse::<usize>() {
+        Ok(jobs) if jobs > 0 => Ok(jobs),
+        _ => Err(format!("Invalid --jobs value \"{value}\". Expected a number above 0")),
     }
 }
 
-fn resolve_log_path(arg: Option<String>) -> Option<PathBuf> {
-    match arg {
-        Some(value) => Some(PathBuf::from(value)),
-        None => goo::app::default_log_path(),
+fn resolve_log_path(arg: Option<String>) -> PathBuf {
+    let resolved = goo::app::resolve_log_path(arg.as_deref());
+    if let Some(warning) = resolved.warning {
+        eprintln!("Warning: {warning}");
     }
+    resolved.path
 }
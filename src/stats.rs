@@ -0,0 +1,417 @@
+use crate::enrich::EnrichedEntry;
+use crate::{parse_timestamp, release_group, WatchEntry};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+/// Watch counts that need nothing beyond the cleaned log data.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchStats {
+    pub total_entries: usize,
+    /// Keyed by lowercased title plus year, so casing differences count as one title.
+    pub titles: BTreeMap<String, TitleCount>,
+    /// Watches per release decade (`1990`, `2000`, ...); entries without a year are skipped.
+    pub decades: BTreeMap<i32, usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TitleCount {
+    pub title: String,
+    pub release_year: Option<i32>,
+    pub watches: usize,
+}
+
+impl WatchStats {
+    /// Counts one more watch of `title`.
+    pub fn record(&mut self, title: &str, release_year: Option<i32>) {
+        self.total_entries += 1;
+        let mut key = title.trim().to_lowercase();
+        if let Some(year) = release_year {
+            key.push('|');
+            key.push_str(&year.to_string());
+        }
+        self.titles
+            .entry(key)
+            .or_insert_with(|| TitleCount {
+                title: title.trim().to_string(),
+                release_year,
+                watches: 0,
+            })
+            .watches += 1;
+        if let Some(year) = release_year {
+            *self.decades.entry(year - year.rem_euclid(10)).or_default() += 1;
+        }
+    }
+
+    pub fn unique_titles(&self) -> usize {
+        self.titles.len()
+    }
+
+    /// The `limit` most watched titles, ties broken alphabetically.
+    pub fn most_watched(&self, limit: usize) -> Vec<&TitleCount> {
+        let mut titles: Vec<&TitleCount> = self.titles.values().collect();
+        titles.sort_by(|a, b| b.watches.cmp(&a.watches).then_with(|| a.title.cmp(&b.title)));
+        titles.truncate(limit);
+        titles
+    }
+}
+
+/// How many titles [`WatchStats::summary`] lists by default.
+pub const DEFAULT_TOP_TITLES: usize = 10;
+
+/// A condensed view of [`WatchStats`], as printed by `goo stats`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct StatsSummary {
+    pub total_entries: usize,
+    pub unique_titles: usize,
+    pub decades: BTreeMap<i32, usize>,
+    pub most_watched: Vec<TitleCount>,
+}
+
+impl WatchStats {
+    /// Totals, decades and the `top` most watched titles.
+    pub fn summary(&self, top: usize) -> StatsSummary {
+        StatsSummary {
+            total_entries: self.total_entries,
+            unique_titles: self.unique_titles(),
+            decades: self.decades.clone(),
+            most_watched: self.most_watched(top).into_iter().cloned().collect(),
+        }
+    }
+}
+
+/// Tallies [`WatchStats`] over a whole history.
+pub fn compute(entries: &[WatchEntry]) -> WatchStats {
+    update(WatchStats::default(), entries)
+}
+
+/// Folds entries appended since `baseline` was computed into it, so a refresh
+/// only pays for the new lines. `update(compute(old), new)` equals
+/// `compute(old + new)`.
+pub fn update(mut baseline: WatchStats, new_entries: &[WatchEntry]) -> WatchStats {
+    for entry in new_entries {
+        baseline.record(&entry.cleaned_title, entry.release_year);
+    }
+    baseline
+}
+
+/// Total screen time per genre, longest first.
+///
+/// Every entry counts as one watch of its movie's full runtime. A movie with
+/// several genres splits that runtime evenly between them so the totals still
+/// add up to the time actually spent watching. Entries without details or a
+/// runtime are ignored.
+pub fn genre_watch_time(entries: &[EnrichedEntry]) -> Vec<(String, Duration)> {
+    let mut totals: HashMap<String, Duration> = HashMap::new();
+    for entry in entries {
+        let Some(details) = entry.details.as_ref() else {
+            continue;
+        };
+        let (Some(runtime), false) = (details.runtime, details.genres.is_empty()) else {
+            continue;
+        };
+        let share = Duration::from_secs(u64::from(runtime) * 60) / details.genres.len() as u32;
+        for genre in &details.genres {
+            *totals.entry(genre.name.clone()).or_default() += share;
+        }
+    }
+
+    let mut totals: Vec<(String, Duration)> = totals.into_iter().collect();
+    totals.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    totals
+}
+
+/// How many watches carried each keyword, most frequent first.
+pub fn top_keywords(entries: &[EnrichedEntry]) -> Vec<(String, usize)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for entry in entries {
+        for keyword in &entry.keywords {
+            *counts.entry(keyword.clone()).or_default() += 1;
+        }
+    }
+
+    let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+/// Watches per director, most watched first. Co-directed films count once
+/// for each director; films with no director listed don't count at all.
+pub fn top_directors(entries: &[EnrichedEntry]) -> Vec<(String, usize)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for entry in entries {
+        for director in &entry.directors {
+            *counts.entry(director.clone()).or_default() += 1;
+        }
+    }
+
+    let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+/// Label for entries whose filename credits no release group.
+pub const UNKNOWN_RELEASE_GROUP: &str = "Unknown";
+
+/// Entries per release group, most common first. Groups are matched
+/// case-insensitively and shown as first seen; untagged entries count as
+/// [`UNKNOWN_RELEASE_GROUP`].
+pub fn release_groups(entries: &[WatchEntry]) -> Vec<(String, usize)> {
+    let mut counts: HashMap<String, (String, usize)> = HashMap::new();
+    for entry in entries {
+        let group = release_group(&entry.raw_title)
+            .unwrap_or_else(|| UNKNOWN_RELEASE_GROUP.to_string());
+        counts.entry(group.to_lowercase()).or_insert((group, 0)).1 += 1;
+    }
+
+    let mut counts: Vec<(String, usize)> = counts.into_values().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+/// Several episodes of one series watched on the same (UTC) day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BingeSession {
+    pub series_title: String,
+    pub episodes: usize,
+    /// `YYYY-MM-DD`.
+    pub date: String,
+}
+
+/// Groups episodes by series and day, keeping days with at least two episodes.
+/// Entries that aren't episodes or have no parseable timestamp are ignored.
+pub fn binge_sessions(entries: &[WatchEntry]) -> Vec<BingeSession> {
+    let mut sessions: BTreeMap<(String, String), BingeSession> = BTreeMap::new();
+    for entry in entries {
+        let Some(episode) = entry.episode.as_ref() else {
+            continue;
+        };
+        let Some(watched) = entry.watched_at.as_deref().and_then(parse_timestamp) else {
+            continue;
+        };
+        let date = format!("{:04}-{:02}-{:02}", watched.year, watched.month, watched.day);
+        sessions
+            .entry((date.clone(), episode.series_title.to_lowercase()))
+            .or_insert_with(|| BingeSession {
+                series_title: episode.series_title.clone(),
+                episodes: 0,
+                date,
+            })
+            .episodes += 1;
+    }
+
+    sessions.into_values().filter(|session| session.episodes > 1).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::enriched;
+    use crate::tmdb::{TmdbGenre, TmdbMovieDetails};
+
+    fn watched(runtime: Option<u32>, genres: &[&str]) -> EnrichedEntry {
+        EnrichedEntry {
+            details: Some(TmdbMovieDetails {
+                id: 1,
+                runtime,
+                genres: genres
+                    .iter()
+                    .enumerate()
+                    .map(|(id, name)| TmdbGenre {
+                        id: id as u32,
+                        name: name.to_string(),
+                    })
+                    .collect(),
+                backdrop_path: None,
+                cast: Vec::new(),
+                director: None,
+                collection: None,
+            }),
+            ..enriched("", None)
+        }
+    }
+
+    fn tagged(keywords: &[&str]) -> EnrichedEntry {
+        EnrichedEntry {
+            keywords: keywords.iter().map(|keyword| keyword.to_string()).collect(),
+            details: None,
+            ..watched(None, &[])
+        }
+    }
+
+    #[test]
+    fn records_titles_and_decades() {
+        let mut stats = WatchStats::default();
+        stats.record("Dune", Some(2021));
+        stats.record("dune", Some(2021));
+        stats.record("Alien", Some(1979));
+        stats.record("Home Video", None);
+
+        assert_eq!(stats.total_entries, 4);
+        assert_eq!(stats.unique_titles(), 3);
+        assert_eq!(stats.decades, BTreeMap::from([(1970, 1), (2020, 2)]));
+        let top = stats.most_watched(1);
+        assert_eq!((top[0].title.as_str(), top[0].watches), ("Dune", 2));
+    }
+
+    #[test]
+    fn counts_release_groups() {
+        let log = [
+            "Heat.1995.1080p.BluRay.x264-SPARKS.mkv",
+            "Alien.1979.1080p.BluRay.x264-sparks.mkv",
+            "Amores.Perros.2000.1080p.BluRay.x264.AAC5.1-[YTS.MX].mp4",
+            "Home Video.mp4",
+        ];
+        let entries: Vec<WatchEntry> =
+            log.iter().filter_map(|line| crate::parse_log_line(line)).collect();
+
+        assert_eq!(
+            release_groups(&entries),
+            vec![
+                ("SPARKS".to_string(), 2),
+                ("Unknown".to_string(), 1),
+                ("YTS.MX".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn incremental_update_matches_full_recompute() {
+        let log = [
+            "2025-01-01T10:00:00Z|Dune.2021.1080p.mkv",
+            "2025-01-02T10:00:00Z|Alien.1979.720p.mkv",
+            "2025-01-03T10:00:00Z|dune.2021.mkv",
+            "2025-01-04T10:00:00Z|Home Video.mp4",
+            "2025-01-05T10:00:00Z|Heat.1995.1080p.BluRay.mkv",
+            "2025-01-06T10:00:00Z|Alien.1979.720p.mkv",
+        ];
+        let entries: Vec<WatchEntry> =
+            log.iter().filter_map(|line| crate::parse_log_line(line)).collect();
+        let full = compute(&entries);
+
+        for split in 0..=entries.len() {
+            let (old, new) = entries.split_at(split);
+            assert_eq!(update(compute(old), new), full, "split at {split}");
+        }
+    }
+
+    #[test]
+    fn splits_runtime_across_genres() {
+        let entries = vec![
+            watched(Some(120), &["Science Fiction", "Drama"]),
+            watched(Some(90), &["Science Fiction"]),
+            watched(None, &["Horror"]),
+        ];
+        let totals = genre_watch_time(&entries);
+
+        assert_eq!(
+            totals,
+            vec![
+                ("Science Fiction".to_string(), Duration::from_secs(150 * 60)),
+                ("Drama".to_string(), Duration::from_secs(60 * 60)),
+            ]
+        );
+    }
+
+    #[test]
+    fn counts_each_rewatch() {
+        let entries = vec![watched(Some(100), &["Comedy"]), watched(Some(100), &["Comedy"])];
+        assert_eq!(
+            genre_watch_time(&entries),
+            vec![("Comedy".to_string(), Duration::from_secs(200 * 60))]
+        );
+    }
+
+    #[test]
+    fn groups_same_day_episodes_into_a_session() {
+        let log = [
+            "2025-03-01T19:00:00Z|Severance.S01E01.1080p.mkv",
+            "2025-03-01T20:00:00Z|Severance.S01E02.1080p.mkv",
+            "2025-03-01T21:05:00Z|Dune.2021.1080p.mkv",
+            "2025-03-01T22:00:00Z|Severance.S01E03.1080p.mkv",
+            "2025-03-02T20:00:00Z|Severance.S01E04.1080p.mkv",
+            "Severance.S01E05.1080p.mkv",
+        ];
+        let entries: Vec<WatchEntry> =
+            log.iter().filter_map(|line| crate::parse_log_line(line)).collect();
+
+        assert_eq!(
+            binge_sessions(&entries),
+            vec![BingeSession {
+                series_title: "Severance".to_string(),
+                episodes: 3,
+                date: "2025-03-01".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn summarizes_totals_decades_and_top_titles() {
+        let entries: Vec<WatchEntry> = [
+            "Dune.2021.mkv",
+            "Alien.1979.mkv",
+            "dune.2021.1080p.mkv",
+            "Heat.1995.mkv",
+            "Home.Movie.mkv",
+        ]
+        .into_iter()
+        .filter_map(crate::parse_log_line)
+        .collect();
+
+        let summary = compute(&entries).summary(2);
+        assert_eq!(summary.total_entries, 5);
+        assert_eq!(summary.unique_titles, 4);
+        assert_eq!(summary.decades, BTreeMap::from([(1970, 1), (1990, 1), (2020, 2)]));
+        let top: Vec<(&str, usize)> = summary
+            .most_watched
+            .iter()
+            .map(|count| (count.title.as_str(), count.watches))
+            .collect();
+        assert_eq!(top, vec![("Dune", 2), ("Alien", 1)]);
+
+        assert_eq!(compute(&[]).summary(DEFAULT_TOP_TITLES), StatsSummary::default());
+    }
+
+    #[test]
+    fn ranks_directors_by_watch_count() {
+        let directed = |directors: &[&str]| EnrichedEntry {
+            directors: directors.iter().map(|name| name.to_string()).collect(),
+            details: None,
+            ..watched(None, &[])
+        };
+        let entries = vec![
+            directed(&["Denis Villeneuve"]),
+            directed(&["Lana Wachowski", "Lilly Wachowski"]),
+            directed(&["Denis Villeneuve"]),
+            directed(&[]),
+            directed(&["Lana Wachowski", "Lilly Wachowski"]),
+            directed(&["Denis Villeneuve"]),
+        ];
+        assert_eq!(
+            top_directors(&entries),
+            vec![
+                ("Denis Villeneuve".to_string(), 3),
+                ("Lana Wachowski".to_string(), 2),
+                ("Lilly Wachowski".to_string(), 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn ranks_keywords_by_watch_count() {
+        let entries = vec![
+            tagged(&["time travel", "heist"]),
+            tagged(&["heist"]),
+            tagged(&[]),
+            tagged(&["dystopia", "heist"]),
+        ];
+        assert_eq!(
+            top_keywords(&entries),
+            vec![
+                ("heist".to_string(), 3),
+                ("dystopia".to_string(), 1),
+                ("time travel".to_string(), 1),
+            ]
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Helpers shared by the unit tests of several modules.
+
+use crate::enrich::{EnrichOptions, EnrichedEntry};
+use crate::tmdb::{MovieSource, TmdbError, TmdbMovie, TmdbTvShow};
+use crate::WatchEntry;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::{mpsc, Mutex};
+
+/// Serves the canned `(status, body)` responses in order, one per connection,
+/// and forwards each raw request head so tests can inspect its shape.
+/// 429 responses carry `Retry-After: 0` so retry tests don't sleep.
+pub fn mock_server(responses: Vec<(u16, &'static str)>) -> (String, mpsc::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock server");
+    let base_url = format!("http://{}", listener.local_addr().expect("local addr"));
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        for (status, body) in responses {
+            let Ok((mut stream, _)) = listener.accept() else {
+                return;
+            };
+            let mut head = Vec::new();
+            let mut byte = [0u8; 1];
+            while !head.ends_with(b"\r\n\r\n") {
+                match stream.read(&mut byte) {
+                    Ok(1) => head.push(byte[0]),
+                    _ => break,
+                }
+            }
+            let _ = sender.send(String::from_utf8_lossy(&head).to_string());
+            let retry_after = if status == 429 { "Retry-After: 0\r\n" } else { "" };
+            let response = format!(
+                "HTTP/1.1 {status} Mock\r\nContent-Type: application/json\r\n{retry_after}Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    (base_url, receiver)
+}
+
+/// A fresh, empty directory under the system temp dir, unique per test name and process.
+pub fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("goo-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("create scratch dir");
+    dir
+}
+
+/// A watch of `title` as the cleaner would leave it, with nothing else parsed.
+pub fn watch(title: &str, year: Option<i32>) -> WatchEntry {
+    WatchEntry {
+        watched_at: None,
+        raw_title: title.to_string(),
+        source_path: None,
+        cleaned_title: title.to_string(),
+        release_year: year,
+        is_stream: false,
+        episode: None,
+        id_hint: None,
+        part: None,
+        position: None,
+        duration: None,
+    }
+}
+
+/// An unmatched [`EnrichedEntry`] for `title`, watched at `watched_at`. Set
+/// the fields a test needs with struct update syntax.
+pub fn enriched(title: &str, watched_at: Option<&str>) -> EnrichedEntry {
+    let entry = WatchEntry {
+        watched_at: watched_at.map(str::to_string),
+        ..watch(title, None)
+    };
+    let source = MockMovieSource::default();
+    EnrichedEntry::from_watch(entry, None, None, None, &source, &EnrichOptions::default())
+}
+
+/// An in-memory [`MovieSource`] with canned films and shows by exact title,
+/// logging every movie lookup so tests can count them without a network.
+#[derive(Default)]
+pub struct MockMovieSource {
+    movies: HashMap<String, TmdbMovie>,
+    shows: HashMap<String, TmdbTvShow>,
+    failing: Vec<String>,
+    lookups: Mutex<Vec<(String, Option<i32>)>>,
+}
+
+impl MockMovieSource {
+    /// Answers searches for exactly `title` with a film of that name and `id`.
+    pub fn with_movie(mut self, title: &str, id: u32) -> Self {
+        let movie = TmdbMovie {
+            id,
+            title: title.to_string(),
+            original_title: None,
+            original_language: None,
+            overview: None,
+            release_date: None,
+            poster_path: None,
+            backdrop_path: None,
+            raw: None,
+        };
+        self.movies.insert(title.to_string(), movie);
+        self
+    }
+
+    /// Answers TV searches for exactly `title` with a show of that name and `id`.
+    pub fn with_show(mut self, title: &str, id: u32) -> Self {
+        let show = TmdbTvShow {
+            id,
+            name: title.to_string(),
+            original_name: None,
+            overview: None,
+            first_air_date: None,
+            poster_path: None,
+            backdrop_path: None,
+        };
+        self.shows.insert(title.to_string(), show);
+        self
+    }
+
+    /// Answers searches for exactly `title` with a TMDB server error.
+    pub fn with_failure(mut self, title: &str) -> Self {
+        self.failing.push(title.to_string());
+        self
+    }
+
+    /// Every `(title, year)` searched so far, in order.
+    pub fn lookups(&self) -> Vec<(String, Option<i32>)> {
+        self.lookups.lock().expect("lookups").clone()
+    }
+}
+
+impl MovieSource for MockMovieSource {
+    fn best_match(&self, title: &str, year: Option<i32>) -> Result<Option<TmdbMovie>, TmdbError> {
+        self.lookups.lock().expect("lookups").push((title.to_string(), year));
+        if self.failing.iter().any(|failing| failing == title) {
+            return Err(TmdbError::HttpStatus {
+                code: 500,
+                body: "Internal error".to_string(),
+            });
+        }
+        Ok(self.movies.get(title).cloned())
+    }
+
+    fn search_tv(&self, title: &str, _year: Option<i32>) -> Result<Vec<TmdbTvShow>, TmdbError> {
+        Ok(self.shows.get(title).cloned().into_iter().collect())
+    }
+}
@@ -0,0 +1,40 @@
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("goo_cli_{name}_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("create scratch dir");
+    dir
+}
+
+fn goo(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_goo"))
+        .args(args)
+        .env("TMDB_API_KEY", "key")
+        .env_remove("GOO_PROXY")
+        .output()
+        .expect("run goo")
+}
+
+#[test]
+fn enrich_fails_on_an_unwritable_cache_unless_in_memory() {
+    let root = scratch_dir("unwritable_cache");
+    let log = root.join("watch.txt");
+    std::fs::write(&log, "# nothing watched yet\n").expect("write log");
+    // A folder path that runs through a file can never be created.
+    std::fs::write(root.join("blocker"), "").expect("write blocker");
+    let cache = root.join("blocker").join("cache.json");
+    let (log, cache) = (log.to_str().expect("log path"), cache.to_str().expect("cache path"));
+
+    let failed = goo(&["enrich", log, cache]);
+    let stderr = String::from_utf8_lossy(&failed.stderr);
+    assert!(stderr.contains("is not writable"), "{stderr}");
+    assert!(failed.stdout.is_empty());
+
+    let in_memory = goo(&["enrich", "--in-memory", log, cache]);
+    let stderr = String::from_utf8_lossy(&in_memory.stderr);
+    assert!(stderr.contains("Cache warning"), "{stderr}");
+    assert_eq!(String::from_utf8_lossy(&in_memory.stdout).trim(), "[]");
+    let _ = std::fs::remove_dir_all(&root);
+}